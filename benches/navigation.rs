@@ -0,0 +1,92 @@
+// Time spent finding the way for hundreds of enemies on a large map, comparing the shared
+// flow field against every enemy searching on its own like they used to.
+// Run with `cargo bench --bench navigation`
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use doomie::map::{flow::FlowField, map::Map, path::PathFinding, pos::MapPos};
+
+const SIZE: usize = 128;
+const ENEMIES: usize = 400;
+const TICKS: u32 = 60;
+
+// open room with a pillar every few tiles
+fn large_map() -> Map {
+    let rows: Vec<String> = (0..SIZE)
+        .map(|y| {
+            (0..SIZE)
+                .map(|x| {
+                    let border = x == 0 || y == 0 || x == SIZE - 1 || y == SIZE - 1;
+                    let pillar = x % 4 == 2 && y % 4 == 2;
+
+                    if border || pillar { 'W' } else { '.' }
+                })
+                .collect()
+        })
+        .collect();
+
+    let text = format!(
+        "[grid]\n{}\n[legend]\n. = floor\nW = wall\n[entities]\nplayer 1.5 1.5 0\n",
+        rows.join("\n")
+    );
+
+    Map::parse(&text).unwrap()
+}
+
+fn enemy_tiles() -> Vec<MapPos> {
+    (0..ENEMIES)
+        .map(|i| MapPos::new(1 + (i * 7) % (SIZE - 2), 1 + (i * 13) % (SIZE - 2)))
+        .filter(|tile| tile.x() % 4 != 2 || tile.y() % 4 != 2)
+        .collect()
+}
+
+// player walking along the top of the map, a new tile every tick
+fn player_tile(tick: u32) -> MapPos {
+    MapPos::new(1 + tick as usize % (SIZE - 2), 1)
+}
+
+fn flow_field(map: &Map, enemies: &[MapPos]) -> Duration {
+    let mut field = FlowField::new(map);
+    let start = Instant::now();
+
+    for tick in 0..TICKS {
+        field.update(player_tile(tick));
+        field.occupy(enemies.iter().copied());
+
+        for &enemy in enemies {
+            black_box(field.next_step(enemy));
+        }
+    }
+
+    start.elapsed()
+}
+
+// A* from each enemy to the player, every tick
+fn search_per_enemy(map: &Map, enemies: &[MapPos]) -> Duration {
+    let pathfinding = PathFinding::new(map);
+    let start = Instant::now();
+
+    for tick in 0..TICKS {
+        for &enemy in enemies {
+            black_box(pathfinding.find_path(enemy, player_tile(tick)));
+        }
+    }
+
+    start.elapsed()
+}
+
+fn main() {
+    let map = large_map();
+    let enemies = enemy_tiles();
+
+    println!("{} enemies on a {}x{} map, {} ticks", enemies.len(), SIZE, SIZE, TICKS);
+
+    for (name, run) in [("flow field", flow_field as fn(&Map, &[MapPos]) -> Duration), ("search per enemy", search_per_enemy)] {
+        let elapsed = run(&map, &enemies);
+
+        println!("{:>18}: {:>10.3} ms per tick", name, elapsed.as_secs_f64() * 1000.0 / TICKS as f64);
+    }
+}
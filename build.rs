@@ -1,7 +1,39 @@
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
 
+// folder holding one folder per enemy kind, each with a folder per animation
+const NPC_SPRITES: &str = "assets/sprites/npc";
+
+// caco_demon -> CacoDemon
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+
+            chars.next().map(|c| c.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+        })
+        .collect()
+}
+
+// sorted subfolders or numbered png frames of a folder
+fn entries(path: &Path, dirs: bool) -> Vec<(String, u32)> {
+    let mut entries: Vec<(String, u32)> = fs::read_dir(path).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir() == dirs)
+        .filter(|path| dirs || path.extension().is_some_and(|ext| ext == "png"))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            let number = path.file_stem()?.to_str()?.parse().unwrap_or(0);
+
+            Some((name, number))
+        })
+        .collect();
+
+    entries.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+    entries
+}
+
 fn main() {
     // Get the output directory where Cargo will place build artifacts
     let out_dir = std::env::var("OUT_DIR").unwrap();
@@ -24,12 +56,12 @@ fn main() {
 
     // Write non-numbered variants
     let simple_variants = [
-        "Wall",
+        "Candlebra",
         "WeaponIdle",
         "WeaponShoot",
-        "SoldierPain",
         "PainScreen",
         "GameOver",
+        "Sky",
         "Zero", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine",
     ];
 
@@ -38,16 +70,52 @@ fn main() {
     }
 
     // Generate numbered variants
+    generate_variants(&mut f, "Wall", 5);
     generate_variants(&mut f, "WeaponReload", 4);
-    generate_variants(&mut f, "SoldierAlive", 8);
-    generate_variants(&mut f, "SoldierDead", 8);
-    generate_variants(&mut f, "SoldierWalking", 4);
-    generate_variants(&mut f, "SoldierShooting", 0);
-    generate_variants(&mut f, "SoldierShooting", 2);
+    generate_variants(&mut f, "GreenLight", 4);
+    generate_variants(&mut f, "RedLight", 4);
+    generate_variants(&mut f, "Fireball", 2);
+
+    // Enemy frames come from the sprite folders, a new kind only needs its folder:
+    // assets/sprites/npc/caco_demon/walk/0.png becomes CacoDemonWalk1
+    let mut npc_textures = Vec::new();
+
+    println!("cargo:rerun-if-changed={}", NPC_SPRITES);
+
+    for (kind, _) in entries(Path::new(NPC_SPRITES), true) {
+        let kind_path = Path::new(NPC_SPRITES).join(&kind);
+
+        println!("cargo:rerun-if-changed={}", kind_path.display());
+
+        for (animation, _) in entries(&kind_path, true) {
+            let animation_path = kind_path.join(&animation);
+
+            println!("cargo:rerun-if-changed={}", animation_path.display());
+
+            for (i, (frame, _)) in entries(&animation_path, false).iter().enumerate() {
+                let variant = format!("{}{}{}", camel_case(&kind), camel_case(&animation), i + 1);
+
+                writeln!(f, "    {},", variant).unwrap();
+
+                npc_textures.push((variant, kind.clone(), animation.clone(), format!("../../{}/{}/{}/{}", NPC_SPRITES, kind, animation, frame)));
+            }
+        }
+    }
 
     // Close the enum definition
     writeln!(f, "}}").unwrap();
 
+    // (texture, kind, animation, path) of every enemy frame, in order
+    let mut f = File::create(Path::new(&out_dir).join("npc_textures.rs")).unwrap();
+
+    writeln!(f, "pub const NPC_TEXTURES: &[(TextureID, &str, &str, &str)] = &[").unwrap();
+
+    for (variant, kind, animation, path) in npc_textures {
+        writeln!(f, "    (TextureID::{}, {:?}, {:?}, {:?}),", variant, kind, animation, path).unwrap();
+    }
+
+    writeln!(f, "];").unwrap();
+
     // Instruct Cargo to rerun the build script if the build script changes
     println!("cargo:rerun-if-changed=build.rs");
 }
@@ -0,0 +1,104 @@
+use crate::{
+    conf::{ENEMY_FLEE_TIME, ENEMY_PAIN_TIME, ENEMY_REACTION_TIME, TICK},
+    enemy_kinds::EnemyDefinition,
+};
+
+// What an enemy is up to. They stand still until they notice the player, take a moment
+// to react and then go after them, attacking whenever they are in range. Kinds with
+// `flee_below` run away once hurt enough, turning to attack every now and then. Getting
+// hit interrupts any of them with a moment of pain
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AiState {
+    Idle,
+    Alert,
+    Chase,
+    Attack,
+    Flee,
+    Pain,
+    Dead,
+}
+
+// What an enemy knows about the player on a given tick
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Senses {
+    pub sees_player: bool,
+    pub heard_gunfire: bool,
+    pub in_range: bool, // close enough to attack, with nothing in between
+    pub life: f32,      // fraction of its full life left
+}
+
+impl AiState {
+    // state for the next tick, after spending `time` seconds in this one
+    pub fn next(self, time: f32, senses: &Senses, kind: &EnemyDefinition) -> AiState {
+        let scared = senses.life < kind.flee_below;
+
+        match self {
+            _ if senses.life <= 0.0 => AiState::Dead,
+            AiState::Dead => AiState::Dead,
+
+            AiState::Idle if senses.sees_player || senses.heard_gunfire => AiState::Alert,
+            AiState::Idle => AiState::Idle,
+
+            AiState::Alert if time >= ENEMY_REACTION_TIME => AiState::Chase,
+            AiState::Alert => AiState::Alert,
+
+            AiState::Chase if scared => AiState::Flee,
+            AiState::Chase if senses.in_range => AiState::Attack,
+            AiState::Chase => AiState::Chase,
+
+            AiState::Attack if time < kind.attack_time => AiState::Attack,
+            AiState::Attack if scared => AiState::Flee,
+            AiState::Attack => AiState::Chase,
+
+            AiState::Flee if senses.in_range && time >= ENEMY_FLEE_TIME => AiState::Attack,
+            AiState::Flee => AiState::Flee,
+
+            AiState::Pain if time < ENEMY_PAIN_TIME => AiState::Pain,
+            AiState::Pain if scared => AiState::Flee,
+            AiState::Pain => AiState::Chase,
+        }
+    }
+}
+
+// Current state and how long it has lasted
+#[derive(Clone, Debug)]
+pub struct Brain {
+    state: AiState,
+    time: f32,
+}
+
+impl Default for Brain {
+    fn default() -> Self {
+        Brain { state: AiState::Idle, time: 0.0 }
+    }
+}
+
+impl Brain {
+    pub fn state(&self) -> AiState {
+        self.state
+    }
+
+    // hit by something, dead enemies don't feel it
+    pub fn hurt(&mut self) {
+        if self.state != AiState::Dead {
+            self.state = AiState::Pain;
+            self.time = 0.0;
+        }
+    }
+
+    // advances one tick, returns true when the state changed
+    pub fn think(&mut self, senses: &Senses, kind: &EnemyDefinition) -> bool {
+        self.time += TICK;
+
+        let next = self.state.next(self.time, senses, kind);
+
+        if next == self.state {
+            return false;
+        }
+
+        self.state = next;
+        self.time = 0.0;
+
+        true
+    }
+}
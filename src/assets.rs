@@ -1,38 +1,30 @@
 use crate::engine::TextureID;
 
+// enemy frames, generated by build.rs from assets/sprites/npc
+include!(concat!(env!("OUT_DIR"), "/npc_textures.rs"));
+
 pub const TEXTURES_PATHS: &[(TextureID, &str)] = &[
-    // soldiers
-    (TextureID::SoldierAlive1, "../../assets/sprites/npc/soldier/idle/0.png"),
-    (TextureID::SoldierAlive2, "../../assets/sprites/npc/soldier/idle/1.png"),
-    (TextureID::SoldierAlive3, "../../assets/sprites/npc/soldier/idle/2.png"),
-    (TextureID::SoldierAlive4, "../../assets/sprites/npc/soldier/idle/3.png"),
-    (TextureID::SoldierAlive5, "../../assets/sprites/npc/soldier/idle/4.png"),
-    (TextureID::SoldierAlive6, "../../assets/sprites/npc/soldier/idle/5.png"),
-    (TextureID::SoldierAlive7, "../../assets/sprites/npc/soldier/idle/6.png"),
-    (TextureID::SoldierAlive8, "../../assets/sprites/npc/soldier/idle/7.png"),
-
-    (TextureID::SoldierDead1,   "../../assets/sprites/npc/soldier/death/0.png"),
-    (TextureID::SoldierDead2,  "../../assets/sprites/npc/soldier/death/1.png"),
-    (TextureID::SoldierDead3,  "../../assets/sprites/npc/soldier/death/2.png"),
-    (TextureID::SoldierDead4,  "../../assets/sprites/npc/soldier/death/3.png"),
-    (TextureID::SoldierDead5,  "../../assets/sprites/npc/soldier/death/4.png"),
-    (TextureID::SoldierDead6, "../../assets/sprites/npc/soldier/death/5.png"),
-    (TextureID::SoldierDead7,  "../../assets/sprites/npc/soldier/death/6.png"),
-    (TextureID::SoldierDead8,  "../../assets/sprites/npc/soldier/death/7.png"),
-
-    (TextureID::SoldierWalking1, "../../assets/sprites/npc/soldier/walk/0.png"),
-    (TextureID::SoldierWalking2, "../../assets/sprites/npc/soldier/walk/1.png"),
-    (TextureID::SoldierWalking3, "../../assets/sprites/npc/soldier/walk/2.png"),
-    (TextureID::SoldierWalking4, "../../assets/sprites/npc/soldier/walk/3.png"),
-
-    (TextureID::SoldierShooting1, "../../assets/sprites/npc/soldier/attack/0.png"),
-    (TextureID::SoldierShooting2, "../../assets/sprites/npc/soldier/attack/1.png"),
-
-    // soldier pain
-    (TextureID::SoldierPain, "../../assets/sprites/npc/soldier/pain/0.png"),
-    
+    // decorations
+    (TextureID::Candlebra,   "../../assets/sprites/static_sprites/candlebra.png"),
+    (TextureID::GreenLight1, "../../assets/sprites/animated_sprites/green_light/0.png"),
+    (TextureID::GreenLight2, "../../assets/sprites/animated_sprites/green_light/1.png"),
+    (TextureID::GreenLight3, "../../assets/sprites/animated_sprites/green_light/3.png"),
+    (TextureID::GreenLight4, "../../assets/sprites/animated_sprites/green_light/4.png"),
+    (TextureID::RedLight1,   "../../assets/sprites/animated_sprites/red_light/0.png"),
+    (TextureID::RedLight2,   "../../assets/sprites/animated_sprites/red_light/1.png"),
+    (TextureID::RedLight3,   "../../assets/sprites/animated_sprites/red_light/2.png"),
+    (TextureID::RedLight4,   "../../assets/sprites/animated_sprites/red_light/3.png"),
+
+    // projectiles
+    (TextureID::Fireball1,   "../../assets/sprites/projectiles/fireball/0.png"),
+    (TextureID::Fireball2,   "../../assets/sprites/projectiles/fireball/1.png"),
+
     // walls
-    (TextureID::Wall,          "../../assets/textures/1.png"),
+    (TextureID::Wall1,         "../../assets/textures/1.png"),
+    (TextureID::Wall2,         "../../assets/textures/2.png"),
+    (TextureID::Wall3,         "../../assets/textures/3.png"),
+    (TextureID::Wall4,         "../../assets/textures/4.png"),
+    (TextureID::Wall5,         "../../assets/textures/5.png"),
 
     // weapon
     (TextureID::WeaponIdle,    "../../assets/sprites/weapon/shotgun/0.png"),
@@ -44,6 +36,7 @@ pub const TEXTURES_PATHS: &[(TextureID, &str)] = &[
 
     (TextureID::PainScreen, "../../assets/textures/blood_screen.png"),
     (TextureID::GameOver, "../../assets/textures/game_over.png"),
+    (TextureID::Sky,      "../../assets/textures/sky.png"),
 
     (TextureID::Zero,  "../../assets/textures/digits/0.png"),
     (TextureID::One,   "../../assets/textures/digits/1.png"),
@@ -55,4 +48,18 @@ pub const TEXTURES_PATHS: &[(TextureID, &str)] = &[
     (TextureID::Seven, "../../assets/textures/digits/7.png"), 
     (TextureID::Eight, "../../assets/textures/digits/8.png"), 
     (TextureID::Nine,  "../../assets/textures/digits/9.png"),
-];
\ No newline at end of file
+];
+
+// every texture the game loads, enemy frames included
+pub fn all_textures() -> impl Iterator<Item = (TextureID, &'static str)> {
+    TEXTURES_PATHS.iter().copied()
+        .chain(NPC_TEXTURES.iter().map(|&(texture_id, _, _, path)| (texture_id, path)))
+}
+
+// frames of one enemy animation in order, like npc_frames("soldier", "walk")
+pub fn npc_frames(kind: &str, animation: &str) -> Vec<TextureID> {
+    NPC_TEXTURES.iter()
+        .filter(|(_, k, a, _)| *k == kind && *a == animation)
+        .map(|(texture_id, ..)| *texture_id)
+        .collect()
+}
@@ -0,0 +1,139 @@
+use std::{collections::HashMap, error::Error, fmt, fs, path::Path};
+
+use crate::engine::Controlls;
+
+// Physical input a control can be bound to. Keys are named after the
+// SDL scancode (the key position, not its label) so the defaults keep their
+// place on AZERTY or any other layout
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Button {
+    Key(String),
+    Mouse(MouseButton),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    X1,
+    X2,
+}
+
+impl fmt::Display for Button {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Button::Key(name) => write!(f, "{}", name),
+            Button::Mouse(button) => write!(f, "Mouse{:?}", button),
+        }
+    }
+}
+
+impl Button {
+    fn parse(name: &str) -> Button {
+        match name.to_lowercase().as_str() {
+            "mouseleft"   => Button::Mouse(MouseButton::Left),
+            "mousemiddle" => Button::Mouse(MouseButton::Middle),
+            "mouseright"  => Button::Mouse(MouseButton::Right),
+            "mousex1"     => Button::Mouse(MouseButton::X1),
+            "mousex2"     => Button::Mouse(MouseButton::X2),
+            _             => Button::Key(name.to_string()),
+        }
+    }
+}
+
+const DEFAULT_BINDINGS: &str = "
+forward      = W
+backward     = S
+strafe_left  = A
+strafe_right = D
+turn_left    = Left
+turn_right   = Right
+strafe       = Left Alt
+run          = Left Shift
+use          = E
+fire         = Space, MouseLeft
+quit         = Escape
+fullscreen   = F11
+";
+
+// Which buttons trigger each control. The file format is one `action = button, button`
+// per line, actions not listed keep their default buttons and # starts a comment
+#[derive(Clone, Debug)]
+pub struct Bindings {
+    controls: HashMap<Controlls, Vec<Button>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let mut bindings = Bindings { controls: HashMap::new() };
+
+        bindings.apply(DEFAULT_BINDINGS).unwrap();
+
+        bindings
+    }
+}
+
+impl Bindings {
+    // a missing file is not an error, the defaults are used instead
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Self::parse(&fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut bindings = Self::default();
+
+        bindings.apply(text)?;
+
+        Ok(bindings)
+    }
+
+    // every button bound to its control, two controls can't share a button
+    pub fn buttons(&self) -> Result<HashMap<Button, Controlls>, Box<dyn Error>> {
+        let mut buttons = HashMap::new();
+
+        for control in Controlls::ALL {
+            for button in self.controls.get(&control).into_iter().flatten() {
+                if let Some(other) = buttons.insert(button.clone(), control) {
+                    return Err(format!("{} is bound to both {} and {}",
+                        button, other.name(), control.name()).into());
+                }
+            }
+        }
+
+        Ok(buttons)
+    }
+
+    fn apply(&mut self, text: &str) -> Result<(), Box<dyn Error>> {
+        for (num, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let (action, buttons) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: expected `action = button`", num + 1))?;
+
+            let control = Controlls::from_name(action.trim())
+                .ok_or_else(|| format!("line {}: unknown action {:?}", num + 1, action.trim()))?;
+
+            let buttons = buttons.split(',')
+                .map(str::trim)
+                .filter(|button| !button.is_empty())
+                .map(Button::parse)
+                .collect();
+
+            self.controls.insert(control, buttons);
+        }
+
+        self.buttons()?;
+
+        Ok(())
+    }
+}
@@ -1,13 +1,13 @@
-use crate::{components::{enemy::Enemy, player::Player}, engine::Sprite, events::Event};
+use crate::{components::player::Player, conf::Config, engine::Sprite, events::Event, spatial::SpatialHash};
 
 pub trait Component {
-    fn update(&mut self, _: &Player, _: Vec<Enemy>) -> Option<Event> {
+    fn update(&mut self, _: &Player, _: &SpatialHash) -> Option<Event> {
         None
     }
 
-    fn visible(&self) -> bool {
+    fn visible(&self, _: &Config) -> bool {
         true
     }
 
-    fn get_sprites(&self) -> Vec<Sprite>;
+    fn get_sprites(&self, config: &Config) -> Vec<Sprite>;
 }
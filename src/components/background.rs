@@ -1,9 +1,25 @@
-use crate::{component::Component, engine::Sprite};
+use crate::{component::Component, conf::Config, engine::Sprite};
 
+use super::player::Player;
+
+// Floor and ceiling, seen from wherever the camera is
 #[derive(Clone, Copy)]
-pub struct Background {}
+pub struct Background {
+    pos: (f32, f32),
+    angle: f32,
+}
 
-impl Component for Background {
-    // TODO: ejercicio: implementar el metodo get_sprites
+impl Background {
+    pub fn new(player: &Player) -> Self {
+        Background {
+            pos: (player.pos().x(), player.pos().y()),
+            angle: player.angle(),
+        }
+    }
+}
 
-}
\ No newline at end of file
+impl Component for Background {
+    fn get_sprites(&self, _: &Config) -> Vec<Sprite> {
+        vec![Sprite::Background(self.pos, self.angle)]
+    }
+}
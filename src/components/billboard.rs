@@ -0,0 +1,59 @@
+use std::f32::consts::{PI, TAU};
+
+use crate::{conf::Config, engine::Sprite, map::pos::Pos, shading::{fog, shade}, texture::TextureRef};
+
+use super::player::Player;
+
+// Where a sprite standing on the map lands on the screen, it always faces the camera
+#[derive(Clone, Copy, Default)]
+pub struct Billboard {
+    screen_x: f32,
+    dist: f32,
+    light: f32, // of the tile it stands on, walls beside it get the same
+}
+
+impl Billboard {
+    pub fn new(pos: Pos, player: &Player, config: &Config) -> Self {
+        let d = pos - player.pos();
+
+        let theta = d.y().atan2(d.x());
+
+        // angle from the view direction, from -PI to PI
+        let delta = (theta - player.angle() + PI).rem_euclid(TAU) - PI;
+
+        let delta_rays = delta / config.delta_angle();
+
+        Billboard {
+            screen_x: (config.half_num_rays() as f32 + delta_rays) * config.scale() as f32,
+            dist: d.y().hypot(d.x()) * delta.cos(), // normalized
+            light: player.map().light(pos),
+        }
+    }
+
+    // could be in view but not visible, as another sprite or wall could be occluding it
+    pub fn visible(&self, texture: TextureRef, config: &Config) -> bool {
+        let image_half_width = (texture.width() / 2) as f32;
+
+        -image_half_width < self.screen_x
+            && self.screen_x < (config.width as f32 + image_half_width)
+            && self.dist > 0.5
+    }
+
+    // scale is the height relative to a wall, shift moves it down as a fraction of that height
+    pub fn sprite(&self, texture: TextureRef, shift: f32, scale: f32, config: &Config) -> Sprite {
+        let proj_height = config.screen_dist() / self.dist * scale;
+        let proj_width = proj_height * texture.ratio();
+        let height_shift = proj_height * shift;
+
+        let sprite_half_width = proj_width / 2.0;
+
+        let location = (
+            self.screen_x - sprite_half_width,
+            config.half_height() as f32 - proj_height / 2.0 + height_shift,
+        );
+
+        let shade = shade(self.light, fog(self.dist, config));
+
+        Sprite::Sprite(self.dist, texture.id(), location, proj_width, proj_height, shade)
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{
+    component::Component,
+    conf::Config,
+    engine::{Sprite, TextureID},
+    events::Event,
+    map::{pos::Pos, spawn::DecorationKind},
+    sequence::Sequence,
+    spatial::SpatialHash,
+    texture::TextureRef,
+};
+
+use super::{billboard::Billboard, player::Player};
+
+// Scenery standing on the floor, it doesn't block the way
+#[derive(Clone)]
+pub struct Decoration {
+    pos: Pos,
+    textures: Sequence,
+
+    shift: f32,
+    scale: f32,
+
+    billboard: Billboard,
+}
+
+impl Decoration {
+    pub fn new(kind: DecorationKind, pos: Pos, textures: Vec<TextureRef>) -> Self {
+        // (seconds per frame, shift, scale)
+        let (frame_time, shift, scale) = match kind {
+            DecorationKind::Candlebra  => (1.0, 0.27, 0.7),
+            DecorationKind::GreenLight => (0.12, 0.16, 0.8),
+            DecorationKind::RedLight   => (0.12, 0.16, 0.8),
+        };
+
+        Decoration {
+            pos,
+            textures: Sequence::new(textures, frame_time),
+            shift,
+            scale,
+            billboard: Billboard::default(),
+        }
+    }
+
+    // copy placed on the screen of the given player
+    pub fn project(&self, player: &Player, config: &Config) -> Decoration {
+        Decoration {
+            billboard: Billboard::new(self.pos, player, config),
+            ..self.clone()
+        }
+    }
+}
+
+impl Component for Decoration {
+    fn update(&mut self, _: &Player, _: &SpatialHash) -> Option<Event> {
+        self.textures.next();
+
+        None
+    }
+
+    fn visible(&self, config: &Config) -> bool {
+        self.billboard.visible(self.textures.get_texture(), config)
+    }
+
+    fn get_sprites(&self, config: &Config) -> Vec<Sprite> {
+        vec![self.billboard.sprite(self.textures.get_texture(), self.shift, self.scale, config)]
+    }
+}
+
+pub fn decoration_textures(kind: DecorationKind) -> &'static [TextureID] {
+    match kind {
+        DecorationKind::Candlebra  => &CANDLEBRA,
+        DecorationKind::GreenLight => &GREEN_LIGHT,
+        DecorationKind::RedLight   => &RED_LIGHT,
+    }
+}
+
+pub static CANDLEBRA: [TextureID; 1] = [TextureID::Candlebra];
+
+pub static GREEN_LIGHT: [TextureID; 4] = [
+    TextureID::GreenLight1,
+    TextureID::GreenLight2,
+    TextureID::GreenLight3,
+    TextureID::GreenLight4,
+];
+
+pub static RED_LIGHT: [TextureID; 4] = [
+    TextureID::RedLight1,
+    TextureID::RedLight2,
+    TextureID::RedLight3,
+    TextureID::RedLight4,
+];
@@ -1,87 +1,76 @@
-use std::f32::consts::{PI, TAU};
+use std::{f32::consts::TAU, rc::Rc};
 
 use crate::{
-    component::Component, components::player::Player, conf::{
-        screen_dist, DELTA_ANGLE, HALF_HEIGHT, HALF_NUM_RAYS, HALF_WIDTH, MAX_DEPTH, SCALE, WIDTH,
-    }, engine::Sprite, events::Event, map::{map::is_wall, path::{PathFinding, WAYS}, pos::{MapPos, Pos}}, sequence::EnemySequence, texture::TextureRef
+    ai::{AiState, Brain, Senses}, component::Component, components::{billboard::Billboard, player::Player, projectile::{Owner, Projectile}}, conf::{
+        Config, PLAYER_RADIUS, TICK,
+    }, enemy_kinds::EnemyDefinition, engine::Sprite, events::Event, observer::EnemyStatus, map::{collision::slide, flow::FlowField, map::Map, path::{shortcut, PathFinding, WAYS}, pos::{MapPos, Pos}, raycasting::line_of_sight}, sequence::{EnemySequence, ROTATIONS}, spatial::{separation, Actor, SpatialHash}, texture::TextureRef
 };
 
-const HIT_MARGIN: f32 = 80.0;
-
-type Projection = ((f32, f32), f32, f32);
+// tiles of the route ahead looked at for a shortcut every tick
+const ROUTE_LOOKAHEAD: usize = 8;
 
 #[derive(Clone)]
 pub struct Enemy {
+    id: usize, // index in the game's enemy list
+    kind: Rc<EnemyDefinition>,
     life: i32,
-    damage: u32,
     pos: Pos,
-
-    pathfinding: PathFinding,
+    facing: f32, // radians, follows where it walks
+    route: Vec<MapPos>, // tiles left on the way to the player, their tile last
 
     sequence: EnemySequence,
+    projectile: Vec<TextureRef>, // empty when it doesn't throw anything
 
-    shift: f32,
-    scale: f32,
-
-    dying: bool,
-    in_pain: bool,
-    moving: bool,
-    shooting: bool,
-
-    shoot_wait: u32,
+    brain: Brain,
+    heard_gunfire: bool, // since the last tick
 
-    screen_x: f32,
-    dist: f32,
+    billboard: Billboard,
+    rotation: usize, // side seen by the camera, 0 is the front
 }
 
 impl Component for Enemy {
-    // could be in view but not visible, as another sprite or wall could be occluding it
-    fn visible(&self) -> bool {
-        let texture = self.get_texture();
-
-        let image_half_width = (texture.width() / 2) as f32;
-
-        -image_half_width < self.screen_x
-            && self.screen_x < (WIDTH as f32 + image_half_width)
-            && self.dist > 0.5
+    fn visible(&self, config: &Config) -> bool {
+        self.billboard.visible(self.get_texture(), config)
     }
 
-    fn get_sprites(&self) -> Vec<Sprite> {
-        let projection = self.get_projection();
-
-        let render = Sprite::Sprite(
-            self.dist,
-            self.get_texture().id(),
-            projection.0,
-            projection.1,
-            projection.2,
-        );
-
-        vec![render]
+    fn get_sprites(&self, config: &Config) -> Vec<Sprite> {
+        vec![self.billboard.sprite(self.get_texture(), self.kind.shift, self.kind.scale, config)]
     }
+}
 
-    fn update(&mut self, player: &Player, enemies: Vec<Enemy>) -> Option<Event> {
-        if self.in_pain {
-            if self.sequence.pain_textures.done() {
-                self.in_pain = false;
-                self.sequence.pain_textures.reset();
-            } else {
-                self.sequence.pain_textures.next();
-            }
-        }
+impl Enemy {
+    pub fn new(
+        id: usize,
+        kind: Rc<EnemyDefinition>,
+        pos: (f32, f32),
+        sequences: EnemySequence,
+        projectile: Vec<TextureRef>,
+    ) -> Self {
+        Enemy {
+            id,
+            life: kind.life,
+            kind,
+            pos: Pos::new(pos.0, pos.1),
+            facing: 0.0,
+            route: Vec::new(),
+            sequence: sequences,
+            projectile,
 
-        if self.shoot_wait == 300 {
-            self.shoot_wait = 0;
-            self.shooting = false;
-        }
+            brain: Brain::default(),
+            heard_gunfire: false,
 
-        if self.shooting {
-            self.shoot_wait += 1;
-            self.sequence.shooting_textures.next();
+            billboard: Billboard::default(),
+            rotation: 0,
         }
+    }
 
-        if self.moving {
-            self.sequence.walking_textures.next();
+    // one tick of thinking and moving, finding the way with its own route and the field
+    // shared by all the enemies, and keeping clear of the living ones in `actors`
+    pub fn act(&mut self, player: &Player, navigation: &FlowField, paths: &PathFinding, actors: &SpatialHash, config: &Config) -> Option<Event> {
+        match self.brain.state() {
+            AiState::Pain                  => self.sequence.pain_textures.next(),
+            AiState::Chase | AiState::Flee => self.sequence.walking_textures.next(),
+            _                              => self.sequence.idle_textures.next(),
         }
 
         if self.life <= 0 {
@@ -92,234 +81,246 @@ impl Component for Enemy {
             }
         }
 
-        self.update_projection(player);
+        self.update_projection(player, config);
+
+        let senses = self.senses(player, config);
+        let changed = self.brain.think(&senses, &self.kind);
+
+        self.heard_gunfire = false;
 
         if self.alive() {
-            let others = enemies
-                .into_iter()
-                .filter(|enemy| enemy.pos != self.pos)
-                .collect();
-
-            if !self.shooting{
-                return self.movement(player, others);
-            } else {
-                return None;
+            self.separate(player, actors);
+        }
+
+        match self.brain.state() {
+            AiState::Alert if changed => self.face(player),
+            AiState::Attack => {
+                let frame = self.sequence.shooting_textures.frame();
+
+                if changed {
+                    self.face(player);
+                    self.sequence.shooting_textures.reset();
+                } else {
+                    self.sequence.shooting_textures.next();
+                }
+
+                // the shot goes off with the frame that shows it
+                let fire_frame = self.fire_frame();
+                let now = self.sequence.shooting_textures.frame();
+
+                if now == fire_frame && (changed || now != frame) {
+                    return Some(self.attack(player));
+                }
             }
+            AiState::Chase => self.chase(player, navigation, paths),
+            AiState::Flee => {
+                self.route.clear();
+                self.flee(player, navigation);
+            }
+            _ => {}
         }
 
         None
     }
-}
 
-impl Enemy {
-    pub fn new(
-        life: i32,
-        damage: u32,
-        pos: (f32, f32),
-        sequences: EnemySequence,
-        shift: f32,
-        scale: f32,
-    ) -> Self {
-        Enemy {
-            life,
-            damage,
-            pos: Pos::new(pos.0, pos.1),
-            sequence: sequences,
-            dying: false,
-            shift,
-            scale,
+    pub fn alive(&self) -> bool {
+        self.life > 0
+    }
 
-            pathfinding: PathFinding::new(),
+    pub fn life(&self) -> i32 {
+        self.life
+    }
 
-            in_pain: false,
-            moving: false,
-            shooting: false,
+    pub fn id(&self) -> usize {
+        self.id
+    }
 
-            shoot_wait: 0,
+    pub fn pos(&self) -> Pos {
+        self.pos
+    }
 
-            screen_x: 0.0,
-            dist: 0.0,
-        }
+    pub fn kind(&self) -> &EnemyDefinition {
+        &self.kind
     }
 
-    pub fn check_dead(&mut self) {
-        if self.life <= 0 && !self.dying {
-            self.dying = true;
+    pub fn status(&self) -> EnemyStatus {
+        match self.brain.state() {
+            _ if !self.alive()             => EnemyStatus::Dead,
+            AiState::Attack                => EnemyStatus::Shooting,
+            AiState::Pain                  => EnemyStatus::Pain,
+            AiState::Chase | AiState::Flee => EnemyStatus::Walking,
+            _                              => EnemyStatus::Idle,
         }
     }
 
-    pub fn alive(&self) -> bool {
-        self.life > 0
-    }
+    fn fire_frame(&self) -> usize {
+        let last = self.sequence.shooting_textures.len().saturating_sub(1);
 
-    fn attack(&self) -> Event {
-        Event::EnemyAttack(self.damage)
+        self.kind.fire_frame.unwrap_or(last).min(last)
     }
 
-    pub fn movement(&mut self, player: &Player, enemies: Vec<Enemy>) -> Option<Event> {
-        if self.near_player(player) && !self.shooting {
-            self.shooting = true;
-            return Some(self.attack());
-        }
-
-        let enemies_pos = enemies
-            .iter()
-            .map(|enemy| (enemy.pos.map()))
-            .map(MapPos::into)
-            .collect();
+    fn attack(&self, player: &Player) -> Event {
+        let sound = self.kind.attack_sound.clone();
 
-        let maybe_path = self.pathfinding.get_path(
-            self.pos.map().into(),
-            player.pos().map().into(),
-            enemies_pos,
-        );
+        if self.kind.projectile.is_none() {
+            let d = player.pos() - self.pos;
+            let chance = self.kind.hit_chance(d.x().hypot(d.y()), player.speed());
 
-        match maybe_path {
-            None => self.moving = false,
-            Some(path) => {
-                self.moving = true;
+            return Event::EnemyAttack(self.kind.damage, chance, sound);
+        }
 
-                let (next_x, next_y) = (path.x(), path.y());
+        let (speed, owner) = (self.kind.projectile_speed, Owner::Enemy(self.id));
+        let projectile = Projectile::new(self.pos, self.facing, speed, self.kind.damage, owner, self.projectile.clone());
 
-                let angle =
-                    (next_y as f32 + 0.5 - self.pos.y()).atan2(next_x as f32 + 0.5 - self.pos.x());
-                let dx = angle.cos() * 0.002;
-                let dy = angle.sin() * 0.002;
+        Event::Launch(projectile, sound)
+    }
 
-                self.pos = self.pos + Pos::new(dx, dy);
-            }
-        }
+    pub fn ai_state(&self) -> AiState {
+        self.brain.state()
+    }
 
-        None
+    // gunfire wakes up enemies that haven't seen the player yet
+    pub fn hear(&mut self) {
+        self.heard_gunfire = true;
     }
 
-    fn get_texture(&self) -> TextureRef {
-        if self.life <= 0 {
-            self.sequence.dying_textures.get_texture()
-        } else if self.shooting {
-            self.sequence.shooting_textures.get_texture()
-        } else if self.in_pain {
-            self.sequence.pain_textures.get_texture()
-        } else {
-            self.sequence.walking_textures.get_texture()
+    fn senses(&self, player: &Player, config: &Config) -> Senses {
+        Senses {
+            sees_player: self.alive() && self.sees(player, config),
+            heard_gunfire: self.heard_gunfire,
+            in_range: self.alive() && self.in_range(player, config),
+            life: self.life as f32 / self.kind.life as f32,
         }
     }
 
-    pub fn hit(&mut self, player: &Player) -> bool {
-        let map_pos = (self.pos.x() as u32, self.pos.y() as u32);
-
-        let d = self.pos - player.pos();
+    fn face(&mut self, player: &Player) {
+        let d = player.pos() - self.pos;
 
-        let theta = d.y().atan2(d.x());
+        self.facing = d.y().atan2(d.x());
+    }
 
-        let in_screen = HALF_WIDTH as f32 - HIT_MARGIN < self.screen_x
-            && self.screen_x < HALF_WIDTH as f32 + HIT_MARGIN;
+    // along the route to the player's tile, found again once they step onto another one
+    // or the enemy was pushed off it. It heads for the furthest tile ahead it can walk
+    // straight to, so it cuts across rooms instead of going from tile to tile
+    fn chase(&mut self, player: &Player, navigation: &FlowField, paths: &PathFinding) {
+        let (map, here, goal) = (player.map(), self.pos.map(), player.pos().map());
+        let radius = self.kind.radius;
 
-        let not_wall_between = self.new_ray(player.pos(), theta, map_pos, player.pos().into());
+        if self.route.first() == Some(&here) && self.route.len() > 1 {
+            self.route.remove(0);
+        }
 
-        if in_screen && not_wall_between {
-            self.in_pain = true;
+        let lost = self.route.first().is_none_or(|tile| tile.x().abs_diff(here.x()).max(tile.y().abs_diff(here.y())) > 1);
 
-            return true;
-        } else {
-            false
+        if lost || self.route.last() != Some(&goal) {
+            // the field already knows when there's no way, A* would search the whole map
+            self.route = navigation.distance(here)
+                .and_then(|_| paths.find_path(here, goal))
+                .map(|route| route[1.min(route.len() - 1)..].to_vec())
+                .unwrap_or_default();
         }
-    }
 
-    pub fn receive_damage(&mut self, damage: i32) {
-        self.life -= damage;
+        let ahead = (1..self.route.len().min(ROUTE_LOOKAHEAD)).rev()
+            .find(|&i| shortcut(map, self.pos, center(self.route[i]), radius));
 
-        self.check_dead();
-    }
-
-    fn new_ray(
-        &self,
-        o: Pos,
-        ray_angle: f32,
-        (x_map, y_map): (u32, u32),
-        (player_x_map, player_y_map): (u32, u32),
-    ) -> bool {
-        if x_map == player_x_map && y_map == player_y_map {
-            return true;
+        if let Some(skip) = ahead {
+            self.route.drain(..skip);
         }
 
-        let (mut wall_dist_v, mut wall_dist_h) = (0.0, 0.0);
-        let (mut player_dist_v, mut player_dist_h) = (0.0, 0.0);
+        if let Some(&tile) = self.route.first() {
+            self.walk_to(center(tile), map);
+        }
+    }
 
-        let (sin_a, cos_a) = (ray_angle.sin(), ray_angle.cos());
+    // to the neighbouring tile furthest from the player, as long as it gets further away
+    fn flee(&mut self, player: &Player, navigation: &FlowField) {
+        let distance = |pos: Pos| {
+            let d = pos - player.pos();
 
-        // horizontals
-        let (mut y_hor, dy) = if sin_a.is_sign_positive() {
-            ((player_y_map + 1) as f32, 1.0)
-        } else {
-            (player_y_map as f32 - 1e-6, -1.0)
+            d.x().hypot(d.y())
         };
 
-        let mut depth_hor = (y_hor - o.y()) / sin_a;
-        let mut x_hor = o.x() + depth_hor * cos_a;
+        let here = self.pos.map();
 
-        let delta_depth = dy / sin_a;
-        let dx = delta_depth * cos_a;
+        let away = WAYS.into_iter()
+            .map(|(dx, dy)| MapPos::new((here.x() as i32 + dx) as usize, (here.y() as i32 + dy) as usize))
+            .filter(|&tile| !player.map().is_wall(center(tile)))
+            .filter(|&tile| !navigation.taken(tile))
+            .map(|tile| (distance(center(tile)), tile))
+            .filter(|(d, _)| *d > distance(self.pos))
+            .max_by(|a, b| a.0.total_cmp(&b.0));
 
-        for _ in 0..MAX_DEPTH {
-            if x_hor as u32 == x_map && y_hor as u32 == y_map {
-                player_dist_h = depth_hor;
-                break;
-            }
+        if let Some((_, tile)) = away {
+            self.walk_to(center(tile), player.map());
+        }
+    }
 
-            if is_wall(Pos::new(x_hor, y_hor)) {
-                wall_dist_h = depth_hor;
-                break;
-            }
+    // out of the enemies it overlaps, each of them moving half of the way, and out of the
+    // player, who doesn't step aside
+    fn separate(&mut self, player: &Player, actors: &SpatialHash) {
+        let radius = self.kind.radius;
+
+        let push = actors.overlapping(self.pos, radius)
+            .filter(|other| other.id != self.id)
+            .map(|other| separation(other, self.pos, radius))
+            .fold(Pos::new(0.0, 0.0), |push, away| push + Pos::new(away.x() / 2.0, away.y() / 2.0));
 
-            x_hor += dx;
-            y_hor += dy;
+        let body = Actor { id: self.id, pos: player.pos(), radius: PLAYER_RADIUS };
+        let push = push + separation(&body, self.pos, radius);
 
-            depth_hor += delta_depth;
+        if push != Pos::new(0.0, 0.0) {
+            self.pos = slide(player.map(), self.pos, push, radius);
         }
+    }
 
-        // verticals
-        let (mut x_vert, dx) = if cos_a.is_sign_positive() {
-            ((player_x_map + 1) as f32, 1.0)
-        } else {
-            (player_x_map as f32 - 1e-6, -1.0)
-        };
+    // one tick towards the point, sliding along the walls in the way
+    fn walk_to(&mut self, target: Pos, map: &Map) {
+        let d = target - self.pos;
+        let angle = d.y().atan2(d.x());
 
-        let mut depth_vert = (x_vert - o.x()) / cos_a;
-        let mut y_vert = o.y() + depth_vert * sin_a;
+        self.facing = angle;
 
-        let delta_depth = dx as f32 / cos_a;
-        let dy = delta_depth * sin_a;
+        let dx = angle.cos() * self.kind.speed * TICK;
+        let dy = angle.sin() * self.kind.speed * TICK;
 
-        for _ in 0..MAX_DEPTH {
-            if x_vert as u32 == x_map && y_vert as u32 == y_map {
-                player_dist_v = depth_vert;
-                break;
-            }
+        self.pos = slide(map, self.pos, Pos::new(dx, dy), self.kind.radius);
+    }
 
-            if is_wall(Pos::new(x_vert, y_vert)) {
-                wall_dist_v = depth_vert;
-                break;
-            }
+    fn get_texture(&self) -> TextureRef {
+        match self.brain.state() {
+            _ if !self.alive()             => self.sequence.dying_textures.get_texture(),
+            AiState::Attack                => self.sequence.shooting_textures.get_texture(),
+            AiState::Pain                  => self.sequence.pain_textures.get_texture(),
+            AiState::Chase | AiState::Flee => self.sequence.walking_textures.get_texture(self.rotation),
+            _                              => self.sequence.idle_textures.get_texture(self.rotation),
+        }
+    }
 
-            x_vert += dx;
-            y_vert += dy;
+    pub fn receive_damage(&mut self, damage: i32) {
+        self.life -= damage;
 
-            depth_vert += delta_depth;
+        if self.alive() {
+            self.brain.hurt();
+            self.sequence.pain_textures.reset();
         }
+    }
 
-        let player_dist = if player_dist_v > player_dist_h {
-            player_dist_v
-        } else {
-            player_dist_h
-        };
-        let wall_dist = if wall_dist_v > wall_dist_h {
-            wall_dist_v
-        } else {
-            wall_dist_h
-        };
+    // no wall between the player and this enemy, as far as the player can see
+    fn sees(&self, player: &Player, config: &Config) -> bool {
+        let d = self.pos - player.pos();
 
-        (0.0 < player_dist && player_dist < wall_dist) || wall_dist == 0.0
+        d.x().hypot(d.y()) <= config.max_depth as f32 && line_of_sight(player.map(), player.pos(), self.pos)
+    }
+
+    // melee kinds only reach the neighbouring tiles, ranged ones need to see the player
+    fn in_range(&self, player: &Player, config: &Config) -> bool {
+        if self.kind.attack_range <= 1.0 {
+            return self.near_player(player);
+        }
+
+        let d = player.pos() - self.pos;
+
+        d.x().hypot(d.y()) <= self.kind.attack_range && self.sees(player, config)
     }
 
     fn near_player(&self, player: &Player) -> bool {
@@ -334,35 +335,31 @@ impl Enemy {
         result
     }
 
-    fn update_projection(&mut self, player: &Player) {
-        let d = self.pos - player.pos();
-
-        let theta = d.y().atan2(d.x());
-        let mut delta = theta - player.angle();
-
-        if (d.x() > 0.0 && player.angle() > PI) || (d.x() < 0.0 && d.y() < 0.0) {
-            delta += TAU;
-        }
-
-        let delta_rays = delta / DELTA_ANGLE;
+    // copy placed between its previous and current position, projected for the given player
+    pub fn interpolate(&self, previous: Pos, player: &Player, alpha: f32, config: &Config) -> Enemy {
+        let mut enemy = self.clone();
 
-        self.screen_x = (HALF_NUM_RAYS as f32 + delta_rays) * SCALE as f32;
+        enemy.pos = previous.lerp(self.pos, alpha);
+        enemy.update_projection(player, config);
 
-        self.dist = d.y().hypot(d.x()) * delta.cos(); // normalized
+        enemy
     }
 
-    fn get_projection(&self) -> Projection {
-        let proj_height = screen_dist() / self.dist * self.scale;
-        let proj_width = proj_height * self.get_texture().ratio();
-        let height_shift = proj_height * self.shift;
-
-        let sprite_half_width = proj_width / 2.0;
+    fn update_projection(&mut self, player: &Player, config: &Config) {
+        self.billboard = Billboard::new(self.pos, player, config);
+        self.rotation = self.rotation(player);
+    }
 
-        let location = (
-            self.screen_x - sprite_half_width,
-            HALF_HEIGHT as f32 - proj_height / 2.0 + height_shift,
-        );
+    // like the classic shooters, the side shown depends on the line from the camera to the
+    // enemy rather than where the camera points, so it doesn't turn as the player looks around
+    fn rotation(&self, player: &Player) -> usize {
+        let d = player.pos() - self.pos;
+        let relative = (self.facing - d.y().atan2(d.x())).rem_euclid(TAU);
 
-        (location, proj_width, proj_height)
+        (relative / (TAU / ROTATIONS as f32)).round() as usize % ROTATIONS
     }
 }
+
+fn center(tile: MapPos) -> Pos {
+    Pos::new(tile.x() as f32 + 0.5, tile.y() as f32 + 0.5)
+}
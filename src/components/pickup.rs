@@ -0,0 +1,80 @@
+use crate::{
+    component::Component,
+    conf::{Config, PICKUP_HEALTH, PICKUP_RADIUS},
+    engine::{Sprite, TextureID},
+    events::Event,
+    map::{pos::Pos, spawn::PickupKind},
+    sequence::Sequence,
+    spatial::SpatialHash,
+    texture::TextureRef,
+};
+
+use super::{billboard::Billboard, decoration::GREEN_LIGHT, player::Player};
+
+// Item lying on the floor, taken as soon as the player walks over it
+#[derive(Clone)]
+pub struct Pickup {
+    kind: PickupKind,
+    pos: Pos,
+    textures: Sequence,
+    taken: bool,
+
+    billboard: Billboard,
+}
+
+impl Pickup {
+    pub fn new(kind: PickupKind, pos: Pos, textures: Vec<TextureRef>) -> Self {
+        Pickup {
+            kind,
+            pos,
+            textures: Sequence::new(textures, 0.12),
+            taken: false,
+            billboard: Billboard::default(),
+        }
+    }
+
+    pub fn taken(&self) -> bool {
+        self.taken
+    }
+
+    // copy placed on the screen of the given player
+    pub fn project(&self, player: &Player, config: &Config) -> Pickup {
+        Pickup {
+            billboard: Billboard::new(self.pos, player, config),
+            ..self.clone()
+        }
+    }
+}
+
+impl Component for Pickup {
+    fn update(&mut self, player: &Player, _: &SpatialHash) -> Option<Event> {
+        self.textures.next();
+
+        let d = self.pos - player.pos();
+
+        if self.taken || d.x().hypot(d.y()) > PICKUP_RADIUS {
+            return None;
+        }
+
+        self.taken = true;
+
+        match self.kind {
+            PickupKind::Health => Some(Event::Heal(PICKUP_HEALTH)),
+        }
+    }
+
+    fn visible(&self, config: &Config) -> bool {
+        !self.taken && self.billboard.visible(self.textures.get_texture(), config)
+    }
+
+    fn get_sprites(&self, config: &Config) -> Vec<Sprite> {
+        vec![self.billboard.sprite(self.textures.get_texture(), 0.5, 0.5, config)]
+    }
+}
+
+// there is no item art yet, health is drawn with the green light
+pub fn pickup_textures(kind: PickupKind) -> &'static [TextureID] {
+    match kind {
+        PickupKind::Health => &GREEN_LIGHT,
+    }
+}
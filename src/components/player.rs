@@ -1,56 +1,102 @@
-use std::f32::consts::TAU;
+use std::{f32::consts::{PI, TAU}, rc::Rc};
 
 use crate::{
     component::Component,
     conf::{
-        DELTA_ANGLE, HALF_FOV, HALF_WIDTH, NUM_RAYS, 
-        PLAYER_ANGLE, PLAYER_POS, PLAYER_SPEED,
+        Config, PLAYER_MAX_LIFE, PLAYER_PAIN_TIME, PLAYER_RADIUS, PLAYER_RUN_FACTOR, SIDE_LIGHT, TICK,
     },
     engine::Sprite,
-    events::Event, map::{map::{is_wall, map_to_texture}, mov::Mov, pos::Pos, raycasting::{calculate_horizontal_intersection, calculate_projection_height, calculate_vertical_intersection, correct_fishbowl_effect, select_closest_intersection}},
+    map::{collision::{collides, slide}, map::{Face, Map}, pos::Pos, raycasting::{calculate_projection_height, cast_ray, correct_fishbowl_effect}},
+    shading::{fog, shade},
+    spatial::{separation, SpatialHash},
 };
 
-use super::enemy::Enemy;
-
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct Player {
     life: u32,
     pos: Pos,
     angle: f32,
     pain: bool,
-    pain_count: u8,
+    pain_time: f32,
+    speed: f32, // tiles per second walked on the last step
+
+    map: Rc<Map>,
 }
 
 impl Player {
-    pub fn new_player() -> Player {
-        return Player {
-            life: 100,
-            pos: Pos::new(PLAYER_POS.0, PLAYER_POS.1),
-            angle: PLAYER_ANGLE,
+    // starts where the map says, maps can't be loaded without a player start
+    pub fn new_player(map: Rc<Map>) -> Player {
+        let (pos, angle) = map.player_start().unwrap();
+
+        Player {
+            life: PLAYER_MAX_LIFE,
+            pos,
+            angle: angle.rem_euclid(TAU),
             pain: false,
-            pain_count: 100,
-        };
+            pain_time: 0.0,
+            speed: 0.0,
+            map,
+        }
     }
 
     pub fn hit(&mut self, damage: u32) {
         self.pain = true;
-        self.life -= damage;
+        self.pain_time = 0.0;
+        self.life = self.life.saturating_sub(damage);
+    }
+
+    // one tick closer to the pain screen going away
+    pub fn recover(&mut self) {
+        if self.pain {
+            self.pain_time += TICK;
+
+            if self.pain_time >= PLAYER_PAIN_TIME {
+                self.pain = false;
+                self.pain_time = 0.0;
+            }
+        }
+    }
+
+    pub fn heal(&mut self, life: u32) {
+        self.life = (self.life + life).min(PLAYER_MAX_LIFE);
     }
 
-    // TODO: ejercicio: escribir estos 3 metodos
     pub fn alive(&self) -> bool {
+        self.life > 0
+    }
 
+    pub fn life(&self) -> u32 {
+        self.life
     }
 
     pub fn pos(&self) -> Pos {
-
+        self.pos
     }
 
     pub fn angle(&self) -> f32 {
+        self.angle
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
 
+    pub fn map(&self) -> &Map {
+        &self.map
     }
     //
 
+    // state between the previous tick and this one, used to render between ticks
+    pub fn interpolate(&self, previous: &Player, alpha: f32) -> Player {
+        let turn = (self.angle - previous.angle + PI).rem_euclid(TAU) - PI;
+
+        Player {
+            pos: previous.pos.lerp(self.pos, alpha),
+            angle: (previous.angle + turn * alpha).rem_euclid(TAU),
+            ..self.clone()
+        }
+    }
+
     pub fn spin(&mut self, angle: f32) {
         if angle.is_sign_positive() {
             if self.angle + angle > TAU {
@@ -69,98 +115,96 @@ impl Player {
         self.angle += angle;
     }
 
-    pub fn walk(&mut self, mov: Mov, delta_time: f32) {
+    // forward and strafe go from -1 to 1 and may come from keys or an analog stick,
+    // the direction is capped to unit length so diagonals are not faster. Living enemies
+    // block the way like walls do
+    pub fn walk(&mut self, forward: f32, strafe: f32, running: bool, delta_time: f32, enemies: &SpatialHash, config: &Config) {
+        let length = f32::hypot(forward, strafe);
+
+        self.speed = 0.0;
+
+        if length == 0.0 {
+            return;
+        }
+
         let (sin_a, cos_a) = (self.angle.sin(), self.angle.cos());
 
-        let speed = PLAYER_SPEED * delta_time;
+        let factor = if running { PLAYER_RUN_FACTOR } else { 1.0 };
 
-        let speed_sin = speed * sin_a;
-        let speed_cos = speed * cos_a;
+        let speed = config.player_speed * factor * delta_time / length.max(1.0);
 
-        let (dx, dy) = match mov {
-            Mov::North => (speed_cos, speed_sin),
-            Mov::South => (-speed_cos, -speed_sin),
-            Mov::West => (speed_sin, -speed_cos),
-            Mov::East => (-speed_sin, speed_cos),
-        };
+        let dx = speed * (forward * cos_a - strafe * sin_a);
+        let dy = speed * (forward * sin_a + strafe * cos_a);
 
-        let mov = self.pos + Pos::new(dx, dy);
+        // walls stop the part of the step going into them, the rest slides along
+        let next = self.push_out(slide(&self.map, self.pos, Pos::new(dx, dy), PLAYER_RADIUS), enemies);
+        let moved = next - self.pos;
 
-        if !is_wall(mov) {
-            self.pos = mov;
-        }
+        self.pos = next;
+        self.speed = moved.x().hypot(moved.y()) / delta_time;
     }
 
-    fn viewport(&self) -> Vec<Sprite> {
-        let mut ray_angle = self.angle - HALF_FOV + 0.0001;
+    // out of the enemies it would step into, going round them. It stays where it was
+    // when there's no room
+    fn push_out(&self, pos: Pos, enemies: &SpatialHash) -> Pos {
+        let pushed = enemies.overlapping(pos, PLAYER_RADIUS)
+            .fold(pos, |pushed, enemy| pushed + separation(enemy, pos, PLAYER_RADIUS));
+
+        let blocked = collides(&self.map, pushed, PLAYER_RADIUS)
+            || enemies.overlapping(pushed, PLAYER_RADIUS).next().is_some();
+
+        if blocked { self.pos } else { pushed }
+    }
+
+    fn viewport(&self, config: &Config) -> Vec<Sprite> {
+        let mut ray_angle = self.angle - config.half_fov() + 0.0001;
         let mut i: usize = 1;
 
-        let mut rays = Vec::with_capacity(NUM_RAYS);
+        let mut rays = Vec::with_capacity(config.num_rays);
 
-        while i < NUM_RAYS {
-            let ray = self.new_ray(i, ray_angle);
-            ray_angle += DELTA_ANGLE;
+        while i < config.num_rays {
+            let ray = self.new_ray(i, ray_angle, config);
+            ray_angle += config.delta_angle();
             i += 1;
-            rays.push(ray);
+            rays.extend(ray);
         }
 
         rays
     }
 
-    fn new_ray(&self, num: usize, ray_angle: f32) -> Sprite {
-        let map_pos = self.pos.map();
-        let screen_dist = HALF_WIDTH as f32 / HALF_FOV.tan();
-        let sin_a = ray_angle.sin();
-        let cos_a = ray_angle.cos();
-    
-        // Calculate horizontal and vertical intersections
-        let (depth_hor, x_hor, texture_hor) =
-            calculate_horizontal_intersection(&self.pos, &map_pos, sin_a, cos_a);
-        let (depth_vert, y_vert, texture_vert) =
-            calculate_vertical_intersection(&self.pos, &map_pos, sin_a, cos_a);
-    
-        // Select the closest intersection
-        let (mut depth, offset, texture) = select_closest_intersection(
-            depth_hor,
-            x_hor,
-            texture_hor,
-            depth_vert,
-            y_vert,
-            texture_vert,
-            sin_a,
-            cos_a,
-        );
-    
+    // None when no wall is found within max_depth tiles
+    fn new_ray(&self, num: usize, ray_angle: f32, config: &Config) -> Option<Sprite> {
+        let hit = cast_ray(&self.map, self.pos, ray_angle, config.max_depth)?;
+        let (sin_a, cos_a) = ray_angle.sin_cos();
+
+        let cell = Pos::new(hit.cell.x() as f32 + 0.5, hit.cell.y() as f32 + 0.5);
+        let texture = self.map.get_texture(cell, hit.side)?;
+
+        // the wall is lit by the sector in front of it, a step back from the hit along the ray
+        let front = Pos::new(self.pos.x() + (hit.distance - 0.01) * cos_a, self.pos.y() + (hit.distance - 0.01) * sin_a);
+        let light = match hit.side {
+            Face::NorthSouth => self.map.light(front),
+            Face::EastWest   => self.map.light(front) * SIDE_LIGHT,
+        };
+
         // Correct for the fishbowl effect and project the wall slice height
-        depth = correct_fishbowl_effect(depth, self.angle, ray_angle);
-        let proj_height = calculate_projection_height(screen_dist, depth);
-    
-        Sprite::Column(
+        let depth = correct_fishbowl_effect(hit.distance, self.angle, ray_angle);
+        let proj_height = calculate_projection_height(config.screen_dist(), depth);
+
+        Some(Sprite::Column(
             depth,
-            map_to_texture(texture.unwrap()),
+            texture,
             num,
-            offset,
+            hit.offset,
             proj_height,
-        )
+            shade(light, fog(depth, config)),
+        ))
     }
 }
 
 impl Component for Player {
-    fn update(&mut self, _: &Player, _: Vec<Enemy>) -> Option<Event> {
-        if self.pain {
-            self.pain_count += 1;
-
-            if self.pain_count == 100 {
-                self.pain = false;
-                self.pain_count = 0;
-            }
-        }
-
-        None
-    }
-
-    fn get_sprites(&self) -> Vec<Sprite> {
-        let mut objects = self.viewport();
+    fn get_sprites(&self, config: &Config) -> Vec<Sprite> {
+        let mut objects = self.viewport(config);
 
         objects.push(Sprite::LifeCounter(self.life));
 
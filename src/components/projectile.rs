@@ -0,0 +1,121 @@
+use crate::{
+    component::Component,
+    conf::{Config, PLAYER_RADIUS, PROJECTILE_RADIUS, TICK},
+    engine::{Sprite, TextureID},
+    events::Event,
+    map::pos::Pos,
+    sequence::Sequence,
+    spatial::{Actor, SpatialHash},
+    texture::TextureRef,
+};
+
+use super::{billboard::Billboard, player::Player};
+
+// flies at about chest height
+const SHIFT: f32 = -0.4;
+const SCALE: f32 = 0.25;
+
+// Who fired a projectile, it never hits its own shooter
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Owner {
+    Player,
+    Enemy(usize), // id of the enemy
+}
+
+// Something thrown across the map, it flies straight until it hits a wall, the
+// player or an enemy other than its owner
+#[derive(Clone)]
+pub struct Projectile {
+    pos: Pos,
+    velocity: Pos, // tiles per second
+    damage: u32,
+    owner: Owner,
+    textures: Sequence,
+    exploded: bool,
+
+    billboard: Billboard,
+}
+
+impl Projectile {
+    pub fn new(pos: Pos, angle: f32, speed: f32, damage: u32, owner: Owner, textures: Vec<TextureRef>) -> Self {
+        Projectile {
+            pos,
+            velocity: Pos::new(angle.cos() * speed, angle.sin() * speed),
+            damage,
+            owner,
+            textures: Sequence::new(textures, 0.1),
+            exploded: false,
+            billboard: Billboard::default(),
+        }
+    }
+
+    pub fn pos(&self) -> Pos {
+        self.pos
+    }
+
+    pub fn exploded(&self) -> bool {
+        self.exploded
+    }
+
+    // copy placed on the screen of the given player, pulled back to where it was
+    // `alpha` of the way through the last tick
+    pub fn project(&self, player: &Player, alpha: f32, config: &Config) -> Projectile {
+        let previous = self.pos - Pos::new(self.velocity.x() * TICK, self.velocity.y() * TICK);
+
+        Projectile {
+            billboard: Billboard::new(previous.lerp(self.pos, alpha), player, config),
+            ..self.clone()
+        }
+    }
+}
+
+impl Component for Projectile {
+    fn update(&mut self, player: &Player, enemies: &SpatialHash) -> Option<Event> {
+        if self.exploded {
+            return None;
+        }
+
+        self.textures.next();
+        self.pos = self.pos + Pos::new(self.velocity.x() * TICK, self.velocity.y() * TICK);
+
+        if player.map().is_wall(self.pos) {
+            self.exploded = true;
+
+            return None;
+        }
+
+        // the player is never in `enemies`, the id doesn't matter
+        let body = Actor { id: 0, pos: player.pos(), radius: PLAYER_RADIUS };
+
+        if self.owner != Owner::Player && body.overlaps(self.pos, PROJECTILE_RADIUS) {
+            self.exploded = true;
+
+            return Some(Event::PlayerHit(self.damage));
+        }
+
+        let target = enemies.overlapping(self.pos, PROJECTILE_RADIUS)
+            .find(|enemy| self.owner != Owner::Enemy(enemy.id))?;
+
+        self.exploded = true;
+
+        Some(Event::EnemyHit(target.id, self.damage as i32))
+    }
+
+    fn visible(&self, config: &Config) -> bool {
+        !self.exploded && self.billboard.visible(self.textures.get_texture(), config)
+    }
+
+    fn get_sprites(&self, config: &Config) -> Vec<Sprite> {
+        vec![self.billboard.sprite(self.textures.get_texture(), SHIFT, SCALE, config)]
+    }
+}
+
+pub static FIREBALL: [TextureID; 2] = [TextureID::Fireball1, TextureID::Fireball2];
+
+// frames of the projectile with the given name, as used in assets/enemies.cfg
+pub fn projectile_textures(name: &str) -> Option<&'static [TextureID]> {
+    match name {
+        "fireball" => Some(&FIREBALL),
+        _          => None,
+    }
+}
@@ -1,13 +1,14 @@
 use crate::{
     component::Component,
-    conf::FPS,
+    conf::Config,
     engine::{Sprite, TextureID},
     events::Event,
     sequence::Sequence,
+    spatial::SpatialHash,
     texture::TextureRef,
 };
 
-use super::{enemy::Enemy, player::Player};
+use super::player::Player;
 
 #[derive(Clone)]
 enum WeaponState {
@@ -29,8 +30,8 @@ impl Weapon {
             damage: 50,
             state: WeaponState::Idle,
             idle: TextureID::WeaponIdle,
-            shoot: Sequence::new(shooting_vec, FPS + 30),
-            reload: Sequence::new(reload_vec, FPS + 30),
+            shoot: Sequence::new(shooting_vec, 1.5),
+            reload: Sequence::new(reload_vec, 1.5),
         }
     }
 
@@ -44,7 +45,7 @@ impl Weapon {
 }
 
 impl Component for Weapon {
-    fn update(&mut self, _: &Player, _: Vec<Enemy>) -> Option<Event> {
+    fn update(&mut self, _: &Player, _: &SpatialHash) -> Option<Event> {
         match self.state {
             WeaponState::Idle => None,
             WeaponState::Shooting => {
@@ -70,7 +71,7 @@ impl Component for Weapon {
         }
     }
 
-    fn get_sprites(&self) -> Vec<Sprite> {
+    fn get_sprites(&self, _: &Config) -> Vec<Sprite> {
         let object = match self.state {
             WeaponState::Idle      => self.idle,
             WeaponState::Shooting  => self.shoot.get_texture_id(),
@@ -1,40 +1,314 @@
+use std::{error::Error, f32::consts::PI, fs, path::Path, str::FromStr};
 
+// the simulation always advances in steps of TICK seconds, whatever the frame rate
+pub const TICK_RATE:u32 = 60;
+pub const TICK:f32 = 1.0 / TICK_RATE as f32;
+// longest frame taken into account, avoids simulating a burst of ticks after a stall
+pub const MAX_FRAME_TIME:f32 = 0.25;
 
-use std::f32::consts::PI;
+pub const PLAYER_MAX_LIFE:u32 = 100;
 
-pub const WIDTH:u32 = 1600;
-pub const HEIGHT:u32 = 900;
+// speeds are in map units (or radians) per second
+pub const PLAYER_ROT_SPEED:f32 = 2.5;
+pub const PLAYER_RUN_FACTOR:f32 = 2.0;
+pub const PLAYER_PAIN_TIME:f32 = 100.0 / 60.0;
+// how close the player gets to walls, in map units
+pub const PLAYER_RADIUS:f32 = 0.2;
 
-pub const HALF_WIDTH:u32 = WIDTH / 2;
-pub const HALF_HEIGHT:u32 = HEIGHT / 2;
+// pickups are taken when the player gets this close, in map units
+pub const PICKUP_RADIUS:f32 = 0.5;
+pub const PICKUP_HEALTH:u32 = 25;
 
-pub const FPS:u32 = 60;
+// size of projectiles, they hit whatever they touch, in map units
+pub const PROJECTILE_RADIUS:f32 = 0.15;
 
-pub const PLAYER_POS:(f32, f32) = (1.5, 5.0);
-pub const PLAYER_SPEED:f32 = 0.004;
-pub const PLAYER_ANGLE:f32 = 0.0;
-pub const PLAYER_ROT_SPEED:f32 = 0.002;
+// enemies wait this long after noticing the player before going after them, in seconds
+pub const ENEMY_REACTION_TIME:f32 = 0.5;
+// enemies flinch this long when hit, in seconds
+pub const ENEMY_PAIN_TIME:f32 = 0.5;
+// a fleeing enemy turns to shoot back this often, in seconds
+pub const ENEMY_FLEE_TIME:f32 = 2.0;
+// gunfire alerts the enemies this close to the player, in map units
+pub const HEARING_RANGE:f32 = 12.0;
+// enemy shots lose this much of their chance to hit for every tile away
+pub const AIM_FALLOFF:f32 = 0.05;
+// and this much for every tile per second the player is moving
+pub const DODGE_FACTOR:f32 = 0.1;
 
-// raycasting
-pub const FOV: f32 = PI / 2.0;
-pub const HALF_FOV: f32 = FOV / 2.0;
-pub const NUM_RAYS: usize = WIDTH as usize / 2;
-pub const HALF_NUM_RAYS: usize = NUM_RAYS / 2;
-pub const DELTA_ANGLE: f32 = FOV / NUM_RAYS as f32;
-pub const MAX_DEPTH: u32 = 20;
+pub const TEXTURE_SIZE: u32 = 256;
 
-pub const SCALE: u32 = WIDTH / NUM_RAYS as u32;
+// times the sky texture wraps around a full turn of the camera
+pub const SKY_REPEAT: f32 = 4.0;
 
-pub const TEXTURE_SIZE: u32 = 256;
+// brightness of the east and west faces of walls, so corners read clearly
+pub const SIDE_LIGHT: f32 = 0.75;
 pub const HALF_TEXTURE_SIZE: u32 = TEXTURE_SIZE / 2;
 
-pub const MOUSE_SENSITIVITY: f32 = 0.0003;
 pub const MOUSE_MAX_REL: i32 = 40;
 pub const MOUSE_BORDER_LEFT: i32 = 100;
-pub const MOUSE_BORDER_RIGHT: i32 = (WIDTH as i32) - MOUSE_BORDER_LEFT;
 
-pub const FLOOR_COLORS: (u32, u32, u32) = (30, 30, 30);
+// window height the HUD textures are drawn at their natural size
+pub const HUD_HEIGHT: u32 = 900;
+
+// Settings that can change without recompiling, read from a file with one
+// `key = value` per line (# starts a comment) and overridable from the command line
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+
+    // raycasting
+    pub fov: f32, // radians, written in degrees in the file
+    pub num_rays: usize,
+    pub max_depth: u32,
+
+    pub player_speed: f32, // map units per second
+    pub mouse_sensitivity: f32, // radians per pixel of mouse motion
+
+    // game controller, sticks and triggers go from -1 to 1
+    pub stick_dead_zone: f32,
+    pub stick_move_sensitivity: f32,
+    pub stick_turn_sensitivity: f32, // radians per second with the stick fully tilted
+    pub trigger_threshold: f32,
+
+    pub floor_color: (u8, u8, u8),
+
+    // things fade into the fog color with distance, about two thirds hidden at fog_falloff map units
+    pub fog_color: (u8, u8, u8),
+    pub fog_falloff: f32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            width: 1600,
+            height: 900,
+            fps: 60,
+            fov: PI / 2.0,
+            num_rays: 800,
+            max_depth: 20,
+            player_speed: 3.6,
+            mouse_sensitivity: 0.018,
+            stick_dead_zone: 0.2,
+            stick_move_sensitivity: 1.0,
+            stick_turn_sensitivity: 3.0,
+            trigger_threshold: 0.5,
+            floor_color: (30, 30, 30),
+            fog_color: (0, 0, 0),
+            fog_falloff: 12.0,
+        }
+    }
+}
+
+impl Config {
+    pub const KEYS: [&'static str; 15] = [
+        "width", "height", "fps", "fov", "num_rays", "max_depth",
+        "player_speed", "mouse_sensitivity",
+        "stick_dead_zone", "stick_move_sensitivity", "stick_turn_sensitivity", "trigger_threshold",
+        "floor_color", "fog_color", "fog_falloff",
+    ];
+
+    // the ones the simulation depends on, demos store them to replay the same way
+    pub const GAMEPLAY_KEYS: [&'static str; 9] = [
+        "width", "fov", "num_rays", "max_depth", "player_speed", "mouse_sensitivity",
+        "stick_dead_zone", "stick_move_sensitivity", "stick_turn_sensitivity",
+    ];
+
+    // a missing file is not an error, the defaults are used instead
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Self::parse(&fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut config = Self::default();
+
+        for (num, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: expected `key = value`", num + 1))?;
+
+            config.set(key.trim(), value.trim())
+                .map_err(|e| format!("line {}: {}", num + 1, e))?;
+        }
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    // changes a single setting, call `validate` once every change is done
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        match key {
+            "width"                  => self.width = parse(key, value)?,
+            "height"                 => self.height = parse(key, value)?,
+            "fps"                    => self.fps = parse(key, value)?,
+            "fov"                    => self.fov = parse::<f32>(key, value)?.to_radians(),
+            "num_rays"               => self.num_rays = parse(key, value)?,
+            "max_depth"              => self.max_depth = parse(key, value)?,
+            "player_speed"           => self.player_speed = parse(key, value)?,
+            "mouse_sensitivity"      => self.mouse_sensitivity = parse(key, value)?,
+            "stick_dead_zone"        => self.stick_dead_zone = parse(key, value)?,
+            "stick_move_sensitivity" => self.stick_move_sensitivity = parse(key, value)?,
+            "stick_turn_sensitivity" => self.stick_turn_sensitivity = parse(key, value)?,
+            "trigger_threshold"      => self.trigger_threshold = parse(key, value)?,
+            "floor_color"            => self.floor_color = parse_color(key, value)?,
+            "fog_color"              => self.fog_color = parse_color(key, value)?,
+            "fog_falloff"            => self.fog_falloff = parse(key, value)?,
+            _ => return Err(format!("unknown setting {:?}", key).into()),
+        }
+
+        Ok(())
+    }
+
+    // a single setting written the way `set` reads it
+    pub fn get(&self, key: &str) -> Option<String> {
+        let value = match key {
+            "width"                  => self.width.to_string(),
+            "height"                 => self.height.to_string(),
+            "fps"                    => self.fps.to_string(),
+            "fov"                    => self.fov.to_degrees().to_string(),
+            "num_rays"               => self.num_rays.to_string(),
+            "max_depth"              => self.max_depth.to_string(),
+            "player_speed"           => self.player_speed.to_string(),
+            "mouse_sensitivity"      => self.mouse_sensitivity.to_string(),
+            "stick_dead_zone"        => self.stick_dead_zone.to_string(),
+            "stick_move_sensitivity" => self.stick_move_sensitivity.to_string(),
+            "stick_turn_sensitivity" => self.stick_turn_sensitivity.to_string(),
+            "trigger_threshold"      => self.trigger_threshold.to_string(),
+            "floor_color"            => format_color(self.floor_color),
+            "fog_color"              => format_color(self.fog_color),
+            "fog_falloff"            => self.fog_falloff.to_string(),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        check(self.width, 1..=16384, "width")?;
+        check(self.height, 1..=16384, "height")?;
+        check(self.fps, 1..=1000, "fps")?;
+        check(self.num_rays, 1..=self.width as usize, "num_rays")?;
+        check(self.max_depth, 1..=1024, "max_depth")?;
+
+        if !(1.0..=179.0).contains(&self.fov.to_degrees().round()) {
+            return Err("fov must be between 1 and 179 degrees".into());
+        }
+
+        if !(self.player_speed > 0.0 && self.player_speed.is_finite()) {
+            return Err("player_speed must be greater than 0".into());
+        }
+
+        if !(self.mouse_sensitivity > 0.0 && self.mouse_sensitivity.is_finite()) {
+            return Err("mouse_sensitivity must be greater than 0".into());
+        }
+
+        // a dead zone of 1 would leave no range for the stick
+        if !(0.0..1.0).contains(&self.stick_dead_zone) {
+            return Err("stick_dead_zone must be at least 0 and less than 1".into());
+        }
+
+        if !(self.stick_move_sensitivity > 0.0 && self.stick_move_sensitivity.is_finite()) {
+            return Err("stick_move_sensitivity must be greater than 0".into());
+        }
+
+        if !(self.stick_turn_sensitivity > 0.0 && self.stick_turn_sensitivity.is_finite()) {
+            return Err("stick_turn_sensitivity must be greater than 0".into());
+        }
+
+        if !(0.0..1.0).contains(&self.trigger_threshold) {
+            return Err("trigger_threshold must be at least 0 and less than 1".into());
+        }
+
+        if !(self.fog_falloff > 0.0 && self.fog_falloff.is_finite()) {
+            return Err("fog_falloff must be greater than 0".into());
+        }
+
+        Ok(())
+    }
+
+    pub fn half_width(&self) -> u32 {
+        self.width / 2
+    }
+
+    pub fn half_height(&self) -> u32 {
+        self.height / 2
+    }
+
+    pub fn half_fov(&self) -> f32 {
+        self.fov / 2.0
+    }
+
+    pub fn half_num_rays(&self) -> usize {
+        self.num_rays / 2
+    }
+
+    pub fn delta_angle(&self) -> f32 {
+        self.fov / self.num_rays as f32
+    }
+
+    // width in pixels of the column drawn for each ray
+    pub fn scale(&self) -> u32 {
+        self.width / self.num_rays as u32
+    }
+
+    pub fn mouse_border_right(&self) -> i32 {
+        self.width as i32 - MOUSE_BORDER_LEFT
+    }
+
+    pub fn screen_dist(&self) -> f32 {
+        self.half_width() as f32 / self.half_fov().tan()
+    }
+
+    // size factor for the weapon and life counter, they grow and shrink with the window height
+    pub fn hud_scale(&self) -> f32 {
+        self.height as f32 / HUD_HEIGHT as f32
+    }
+
+    // new window size, the columns keep their width so the ray count follows the window width
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let scale = self.scale();
+
+        self.width = width.max(1);
+        self.height = height.max(1);
+        self.num_rays = (self.width / scale).max(1) as usize;
+    }
+}
+
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, Box<dyn Error>> {
+    value.parse().map_err(|_| format!("invalid value {:?} for {}", value, key).into())
+}
+
+fn parse_color(key: &str, value: &str) -> Result<(u8, u8, u8), Box<dyn Error>> {
+    let rgb = value.split(',')
+        .map(|c| parse(key, c.trim()))
+        .collect::<Result<Vec<u8>, _>>()?;
+
+    match rgb.as_slice() {
+        [r, g, b] => Ok((*r, *g, *b)),
+        _ => Err(format!("{} must be three numbers `r, g, b`", key).into()),
+    }
+}
+
+fn format_color((r, g, b): (u8, u8, u8)) -> String {
+    format!("{},{},{}", r, g, b)
+}
 
-pub fn screen_dist() -> f32 {
-    HALF_WIDTH as f32 / HALF_FOV.tan()
+fn check<T: PartialOrd + std::fmt::Display>(value: T, range: std::ops::RangeInclusive<T>, key: &str) -> Result<(), Box<dyn Error>> {
+    if range.contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("{} must be between {} and {}, got {}", key, range.start(), range.end(), value).into())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,262 @@
+use std::{error::Error, fmt, fs, path::Path};
+
+use crate::{conf::Config, engine::Controlls, input::InputState, map::map::Map};
+
+// Text format, one record per line:
+//
+//   doomie-demo 5
+//   map <checksum>          checksum of the map file it was recorded on
+//   set <key> <value>       setting the simulation depends on, as in the config file
+//   i <tick> <held> <pressed> <released> <mouse x> <mouse dx> <forward> <strafe> <turn>
+//                           input polled on that tick, controls are comma separated
+//                           or - when empty. Ticks where nothing changed are omitted
+//   c <tick> <checksum>     world checksum after that tick was simulated
+//   end <tick>              tick where the recording stopped
+const MAGIC: &str = "doomie-demo";
+const VERSION: u32 = 5;
+
+// a checksum of the world is stored every this many ticks
+pub const CHECKSUM_INTERVAL: u32 = 60;
+
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct Demo {
+    map: u32,
+    settings: Vec<(String, String)>,
+    inputs: Vec<(u32, InputState)>,
+    checksums: Vec<(u32, u32)>,
+    end: u32,
+}
+
+impl Demo {
+    // empty recording of a game of `map` played with `config`
+    pub fn new(map: &Map, config: &Config) -> Self {
+        let settings = Config::GAMEPLAY_KEYS.into_iter()
+            .map(|key| (key.to_string(), config.get(key).unwrap()))
+            .collect();
+
+        Demo { map: map.checksum(), settings, ..Self::default() }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_string())?;
+
+        Ok(())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut lines = text.lines().enumerate();
+
+        match lines.next().map(|(_, line)| line.split_whitespace().collect::<Vec<_>>()) {
+            Some(header) if header == [MAGIC, &VERSION.to_string()] => {}
+            Some(header) if header.first() == Some(&MAGIC) => {
+                return Err(format!("unsupported demo version {:?}", header.get(1)).into())
+            }
+            _ => return Err("not a demo file".into()),
+        }
+
+        let mut demo = Demo::default();
+
+        for (num, line) in lines {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            demo.parse_record(&fields)
+                .map_err(|e| format!("line {}: {}", num + 1, e))?;
+        }
+
+        Ok(demo)
+    }
+
+    fn parse_record(&mut self, fields: &[&str]) -> Result<(), Box<dyn Error>> {
+        match fields {
+            [] => {}
+            ["map", checksum] => self.map = u32::from_str_radix(checksum, 16)?,
+            ["set", key, value @ ..] if !value.is_empty() => {
+                self.settings.push((key.to_string(), value.join(" ")));
+            }
+            ["i", tick, held, pressed, released, x, dx, forward, strafe, turn] => {
+                let input = InputState::from_parts(
+                    &parse_controls(held)?,
+                    &parse_controls(pressed)?,
+                    &parse_controls(released)?,
+                    (x.parse()?, dx.parse()?),
+                    [forward.parse()?, strafe.parse()?, turn.parse()?],
+                );
+
+                self.inputs.push((tick.parse()?, input));
+            }
+            ["c", tick, checksum] => {
+                self.checksums.push((tick.parse()?, u32::from_str_radix(checksum, 16)?));
+            }
+            ["end", tick] => self.end = tick.parse()?,
+            _ => return Err("unknown record".into()),
+        }
+
+        Ok(())
+    }
+
+    // polls where nothing changed are implied by the previous one
+    pub fn record_input(&mut self, tick: u32, input: &InputState) {
+        let last_axes = self.inputs.last().map(|(_, last)| last.axes()).unwrap_or_default();
+
+        if !input.is_steady() || input.axes() != last_axes {
+            self.inputs.push((tick, input.clone()));
+        }
+    }
+
+    pub fn record_checksum(&mut self, tick: u32, checksum: u32) {
+        self.checksums.push((tick, checksum));
+    }
+
+    pub fn finish(&mut self, tick: u32) {
+        self.end = tick;
+    }
+
+    pub fn end(&self) -> u32 {
+        self.end
+    }
+
+    // `config` with the settings the demo was recorded with, as long as it was recorded
+    // on `map`. The window size is left to the player, the demo must match it
+    pub fn configure(&self, map: &Map, config: &Config) -> Result<Config, Box<dyn Error>> {
+        if self.map != map.checksum() {
+            return Err("the demo was recorded on another map".into());
+        }
+
+        let mut replay = *config;
+
+        for (key, value) in &self.settings {
+            if key == "width" && config.get(key).as_ref() != Some(value) {
+                return Err(format!("the demo was recorded {} pixels wide, the window is {}", value, config.width).into());
+            }
+
+            replay.set(key, value)
+                .map_err(|e| format!("demo setting {}: {}", key, e))?;
+        }
+
+        replay.validate()?;
+
+        Ok(replay)
+    }
+}
+
+impl fmt::Display for Demo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} {}", MAGIC, VERSION)?;
+        writeln!(f, "map {:08x}", self.map)?;
+
+        for (key, value) in &self.settings {
+            writeln!(f, "set {} {}", key, value)?;
+        }
+
+        for (tick, input) in &self.inputs {
+            let [forward, strafe, turn] = input.axes();
+
+            writeln!(f, "i {} {} {} {} {} {} {} {} {}",
+                tick,
+                format_controls(|control| input.held(control)),
+                format_controls(|control| input.pressed(control)),
+                format_controls(|control| input.released(control)),
+                input.mouse_x(),
+                input.mouse_dx(),
+                forward, strafe, turn)?;
+        }
+
+        for (tick, checksum) in &self.checksums {
+            writeln!(f, "c {} {:08x}", tick, checksum)?;
+        }
+
+        writeln!(f, "end {}", self.end)
+    }
+}
+
+// the world checksum stored in the demo didn't match the replayed one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Divergence {
+    pub tick: u32,
+    pub expected: u32,
+    pub actual: u32,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "replay diverged at tick {}: expected checksum {:08x}, got {:08x}",
+            self.tick, self.expected, self.actual)
+    }
+}
+
+// Feeds a demo back into the game tick by tick
+pub struct Playback {
+    demo: Demo,
+    next_input: usize,
+    input: InputState,
+    divergence: Option<Divergence>,
+}
+
+impl Playback {
+    pub fn new(demo: Demo) -> Self {
+        Playback { demo, next_input: 0, input: InputState::new(), divergence: None }
+    }
+
+    pub fn poll(&mut self, tick: u32) -> InputState {
+        self.input = match self.demo.inputs.get(self.next_input) {
+            Some((input_tick, input)) if *input_tick == tick => {
+                self.next_input += 1;
+                input.clone()
+            }
+            _ => self.input.steady(),
+        };
+
+        self.input.clone()
+    }
+
+    // only the first divergence is kept, everything after it is noise
+    pub fn check(&mut self, tick: u32, actual: u32) {
+        if self.divergence.is_some() {
+            return;
+        }
+
+        let expected = self.demo.checksums.iter().find(|(t, _)| *t == tick);
+
+        if let Some(&(_, expected)) = expected {
+            if expected != actual {
+                self.divergence = Some(Divergence { tick, expected, actual });
+            }
+        }
+    }
+
+    pub fn finished(&self, tick: u32) -> bool {
+        tick >= self.demo.end && self.next_input >= self.demo.inputs.len()
+    }
+
+    pub fn divergence(&self) -> Option<Divergence> {
+        self.divergence
+    }
+}
+
+fn format_controls(filter: impl Fn(Controlls) -> bool) -> String {
+    let names: Vec<&str> = Controlls::ALL.into_iter()
+        .filter(|control| filter(*control))
+        .map(|control| control.name())
+        .collect();
+
+    if names.is_empty() {
+        "-".to_string()
+    } else {
+        names.join(",")
+    }
+}
+
+fn parse_controls(field: &str) -> Result<Vec<Controlls>, Box<dyn Error>> {
+    if field == "-" {
+        return Ok(Vec::new());
+    }
+
+    field.split(',').map(|name| {
+        Controlls::from_name(name)
+            .ok_or_else(|| format!("unknown control {:?}", name).into())
+    }).collect()
+}
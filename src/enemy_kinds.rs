@@ -0,0 +1,203 @@
+use std::{collections::HashMap, error::Error, rc::Rc};
+
+use crate::{assets::npc_frames, components::projectile::projectile_textures, conf::{AIM_FALLOFF, DODGE_FACTOR}};
+
+// bundled with the game, new kinds only need their section there and their frames
+// under assets/sprites/npc/<name>
+const ENEMIES: &str = include_str!("../assets/enemies.cfg");
+
+// Stats of one enemy kind, see assets/enemies.cfg for what each one means
+#[derive(Clone, PartialEq, Debug)]
+pub struct EnemyDefinition {
+    pub life: i32,
+    pub damage: u32,
+    pub speed: f32,
+    pub radius: f32, // how close it gets to walls
+    pub attack_range: f32,
+    pub attack_time: f32,
+    pub fire_frame: Option<usize>, // attack frame that shoots, None is the last one
+    pub accuracy: f32, // chance to hit from up close, for kinds without a projectile
+    pub flee_below: f32, // fraction of its life, 0 never runs away
+    pub shift: f32,
+    pub scale: f32,
+    pub projectile: Option<String>, // None shoots, hitting or missing at once
+    pub projectile_speed: f32,
+    pub attack_sound: String,
+    pub pain_sound: String,
+    pub death_sound: String,
+}
+
+impl Default for EnemyDefinition {
+    fn default() -> Self {
+        EnemyDefinition {
+            life: 100,
+            damage: 5,
+            speed: 0.12,
+            radius: 0.3,
+            attack_range: 8.0,
+            attack_time: 2.0,
+            fire_frame: None,
+            accuracy: 0.9,
+            flee_below: 0.0,
+            shift: 0.27,
+            scale: 0.7,
+            projectile: None,
+            projectile_speed: 4.0,
+            attack_sound: "npc_attack".to_string(),
+            pain_sound: "npc_pain".to_string(),
+            death_sound: "npc_death".to_string(),
+        }
+    }
+}
+
+impl EnemyDefinition {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        match key {
+            "life"             => self.life = parse(key, value)?,
+            "damage"           => self.damage = parse(key, value)?,
+            "speed"            => self.speed = parse(key, value)?,
+            "radius"           => self.radius = parse(key, value)?,
+            "attack_range"     => self.attack_range = parse(key, value)?,
+            "attack_time"      => self.attack_time = parse(key, value)?,
+            "fire_frame"       => self.fire_frame = Some(parse(key, value)?),
+            "accuracy"         => self.accuracy = parse(key, value)?,
+            "flee_below"       => self.flee_below = parse(key, value)?,
+            "shift"            => self.shift = parse(key, value)?,
+            "scale"            => self.scale = parse(key, value)?,
+            "projectile"       => self.projectile = Some(value.to_string()),
+            "projectile_speed" => self.projectile_speed = parse(key, value)?,
+            "attack_sound"     => self.attack_sound = value.to_string(),
+            "pain_sound"       => self.pain_sound = value.to_string(),
+            "death_sound"      => self.death_sound = value.to_string(),
+            _ => return Err(format!("unknown setting {:?}", key).into()),
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.life <= 0 {
+            return Err("life must be greater than 0".into());
+        }
+
+        if !(self.speed >= 0.0 && self.speed.is_finite()) {
+            return Err("speed can't be negative".into());
+        }
+
+        // wider ones couldn't go through a corridor one tile wide
+        if !(self.radius > 0.0 && self.radius < 0.5) {
+            return Err("radius must be between 0 and 0.5".into());
+        }
+
+        if !(self.attack_range >= 1.0 && self.attack_range.is_finite()) {
+            return Err("attack_range must be at least 1".into());
+        }
+
+        if !(self.attack_time > 0.0 && self.attack_time.is_finite()) {
+            return Err("attack_time must be greater than 0".into());
+        }
+
+        if !(0.0..=1.0).contains(&self.accuracy) {
+            return Err("accuracy must be between 0 and 1".into());
+        }
+
+        if !(0.0..=1.0).contains(&self.flee_below) {
+            return Err("flee_below must be between 0 and 1".into());
+        }
+
+        if !(self.scale > 0.0 && self.scale.is_finite()) {
+            return Err("scale must be greater than 0".into());
+        }
+
+        if let Some(name) = &self.projectile {
+            projectile_textures(name).ok_or_else(|| format!("unknown projectile {:?}", name))?;
+        }
+
+        if !(self.projectile_speed > 0.0 && self.projectile_speed.is_finite()) {
+            return Err("projectile_speed must be greater than 0".into());
+        }
+
+        Ok(())
+    }
+
+    // chance a shot hits a player `distance` tiles away moving at `speed` tiles per second
+    pub fn hit_chance(&self, distance: f32, speed: f32) -> f32 {
+        (self.accuracy - AIM_FALLOFF * distance - DODGE_FACTOR * speed).clamp(0.0, 1.0)
+    }
+}
+
+// Definition of every enemy kind by name, the one maps use. The format is a `[kind]` line
+// followed by its `key = value` settings, settings left out keep the soldier values and
+// # starts a comment
+#[derive(Clone, Debug)]
+pub struct EnemyKinds {
+    definitions: HashMap<String, Rc<EnemyDefinition>>,
+}
+
+impl EnemyKinds {
+    // the ones in assets/enemies.cfg
+    pub fn bundled() -> Result<Self, Box<dyn Error>> {
+        Self::parse(ENEMIES).map_err(|e| format!("assets/enemies.cfg: {}", e).into())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut definitions = HashMap::new();
+        let mut current: Option<(String, EnemyDefinition)> = None;
+
+        for (num, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                let kind = name.trim().to_string();
+
+                if npc_frames(&kind, "walk").is_empty() {
+                    return Err(format!("line {}: no sprites for {:?} in assets/sprites/npc", num + 1, kind).into());
+                }
+
+                if definitions.contains_key(&kind) || current.as_ref().is_some_and(|(other, _)| *other == kind) {
+                    return Err(format!("line {}: {} is defined twice", num + 1, name).into());
+                }
+
+                if let Some((kind, definition)) = current.replace((kind, EnemyDefinition::default())) {
+                    definitions.insert(kind, definition);
+                }
+
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| format!("line {}: expected `key = value` or `[kind]`", num + 1))?;
+
+            let (_, definition) = current.as_mut()
+                .ok_or_else(|| format!("line {}: settings must follow a `[kind]` line", num + 1))?;
+
+            definition.set(key.trim(), value.trim())
+                .map_err(|e| format!("line {}: {}", num + 1, e))?;
+        }
+
+        definitions.extend(current);
+
+        for (kind, definition) in &definitions {
+            definition.validate().map_err(|e| format!("{}: {}", kind, e))?;
+        }
+
+        let definitions = definitions.into_iter()
+            .map(|(kind, definition)| (kind, Rc::new(definition)))
+            .collect();
+
+        Ok(EnemyKinds { definitions })
+    }
+
+    // None when there's no such kind
+    pub fn get(&self, kind: &str) -> Option<Rc<EnemyDefinition>> {
+        self.definitions.get(kind).cloned()
+    }
+}
+
+fn parse<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, Box<dyn Error>> {
+    value.parse().map_err(|_| format!("invalid value {:?} for {}", value, key).into())
+}
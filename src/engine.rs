@@ -1,17 +1,16 @@
-use std::f32::{INFINITY, NEG_INFINITY};
+use std::rc::Rc;
 
-use crate::texture::TextureRef;
+use crate::{input::InputState, map::map::Map, shading::Shade, texture::TextureRef};
 
 pub trait Driver {
     fn delta_time(&mut self) -> f32;
 
-    // controls
-    fn poll(&mut self) -> Option<Controlls>;
+    // controls, drains every pending event and returns the input since the previous poll
+    fn poll(&mut self) -> InputState;
 
     // audio
     fn play_shoot(&mut self);
-    fn play_pain_shout(&mut self);
-    fn play_enemy_shoot(&mut self);
+    fn play_sound(&mut self, name: &str); // assets/sound/<name>.wav
     fn play_player_pain(&mut self);
 
     // rendering
@@ -20,17 +19,23 @@ pub trait Driver {
     fn get_image_ratio(&self, texture_id: TextureID) -> f32;
     fn center_mouse(&self, x: i32, y: i32);
     fn load_refs(&self, ids: &[TextureID]) -> Vec<TextureRef>;
+    // level whose floor and ceiling are drawn behind everything
+    fn set_map(&mut self, map: Rc<Map>);
+
+    // window
+    fn screen_size(&self) -> (u32, u32);
+    fn toggle_fullscreen(&mut self);
 }
 
 #[derive(Clone, Copy)]
 pub enum Sprite {
-    Background,
+    Background((f32, f32), f32), // camera position and angle
     PainScreen,
     GameOver,
     LifeCounter(u32),
     DrawWeapon(TextureID),
-    Column(f32, TextureID, usize, f32, f32),
-    Sprite(f32, TextureID, (f32, f32), f32, f32),
+    Column(f32, TextureID, usize, f32, f32, Shade),
+    Sprite(f32, TextureID, (f32, f32), f32, f32, Shade),
 }
 
 impl Sprite {
@@ -38,15 +43,84 @@ impl Sprite {
         match self {
             Sprite::Column(z_order, ..) => *z_order,
             Sprite::Sprite(z_order, ..) => *z_order,
-            Sprite::Background          => INFINITY,
-            Sprite::DrawWeapon(_)       => NEG_INFINITY,
-            Sprite::PainScreen          => NEG_INFINITY,
-            Sprite::LifeCounter(_)      => NEG_INFINITY,
-            Sprite::GameOver            => NEG_INFINITY,
+            Sprite::Background(..)      => f32::INFINITY,
+            Sprite::DrawWeapon(_)       => f32::NEG_INFINITY,
+            Sprite::PainScreen          => f32::NEG_INFINITY,
+            Sprite::LifeCounter(_)      => f32::NEG_INFINITY,
+            Sprite::GameOver            => f32::NEG_INFINITY,
+        }
+    }
+}
+
+// Distance to the wall drawn on every screen column, built from the raycaster output.
+// Sprites are clipped against it so they can be partly hidden behind a corner
+pub struct DepthBuffer {
+    depths: Vec<f32>,
+}
+
+impl DepthBuffer {
+    // each ray covers column_width pixels, columns with no wall are infinitely far
+    pub fn new(commands: &[Sprite], width: u32, column_width: u32) -> Self {
+        let mut depths = vec![f32::INFINITY; width as usize];
+
+        for command in commands {
+            if let Sprite::Column(depth, _, num, ..) = command {
+                let start = (num * column_width as usize).min(depths.len());
+                let end = (start + column_width as usize).min(depths.len());
+
+                depths[start..end].fill(*depth);
+            }
+        }
+
+        DepthBuffer { depths }
+    }
+
+    pub fn depth(&self, x: usize) -> f32 {
+        self.depths.get(x).copied().unwrap_or(f32::INFINITY)
+    }
+
+    // runs of screen columns [start, end) of a sprite drawn from x to x + width
+    // that are closer than the walls behind them
+    pub fn visible_spans(&self, x: i32, width: u32, depth: f32) -> Vec<(i32, i32)> {
+        let start = x.max(0);
+        let end = (x as i64 + width as i64).min(self.depths.len() as i64) as i32;
+
+        let mut spans = Vec::new();
+        let mut span_start = None;
+
+        for column in start..end {
+            let visible = depth < self.depths[column as usize];
+
+            match (visible, span_start) {
+                (true, None) => span_start = Some(column),
+                (false, Some(first)) => {
+                    spans.push((first, column));
+                    span_start = None;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(first) = span_start {
+            spans.push((first, end));
         }
+
+        spans
     }
 }
 
+// part of a texture `texture_width` wide seen through the screen span [start, end)
+// of a sprite drawn from x to x + width, as (texture x, texture width)
+pub fn span_source(span: (i32, i32), x: i32, width: u32, texture_width: u32) -> (i32, u32) {
+    let to_texture = |column: i32| {
+        ((column - x) as i64 * texture_width as i64 / width.max(1) as i64) as i32
+    };
+
+    let (start, end) = (to_texture(span.0), to_texture(span.1));
+
+    (start, (end - start).max(1) as u32)
+}
+
 // Include the generated enum definition
 include!(concat!(env!("OUT_DIR"), "/texture_id.rs"));
 
@@ -111,15 +185,71 @@ pub fn digit_to_texture(c: char) -> TextureID {
     }
 }
 
-#[derive(Clone, Copy)]
-pub enum Controlls {
-    ArrowUp,
-    ArrowDown,
-    ArrowLeft,
-    ArrowRight,
+// walls are numbered after their file in assets/textures
+pub fn wall_texture(number: u32) -> Option<TextureID> {
+    match number {
+        1 => Some(TextureID::Wall1),
+        2 => Some(TextureID::Wall2),
+        3 => Some(TextureID::Wall3),
+        4 => Some(TextureID::Wall4),
+        5 => Some(TextureID::Wall5),
+        _ => None,
+    }
+}
 
-    Enter,
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Controlls {
+    ArrowUp,    // forward
+    ArrowDown,  // backward
+    ArrowLeft,  // strafe left
+    ArrowRight, // strafe right
+
+    TurnLeft,
+    TurnRight,
+    Strafe, // while held the turn controls strafe instead
+    Run,
+    Use, // doors and switches, nothing in the maps can be used yet
+
+    Enter, // fire
     Escape,
+    Fullscreen,
+}
+
+impl Controlls {
+    pub const ALL: [Controlls; 12] = [
+        Controlls::ArrowUp,
+        Controlls::ArrowDown,
+        Controlls::ArrowLeft,
+        Controlls::ArrowRight,
+        Controlls::TurnLeft,
+        Controlls::TurnRight,
+        Controlls::Strafe,
+        Controlls::Run,
+        Controlls::Use,
+        Controlls::Enter,
+        Controlls::Escape,
+        Controlls::Fullscreen,
+    ];
+
+    // name of the action in bindings and demo files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Controlls::ArrowUp    => "forward",
+            Controlls::ArrowDown  => "backward",
+            Controlls::ArrowLeft  => "strafe_left",
+            Controlls::ArrowRight => "strafe_right",
+            Controlls::TurnLeft   => "turn_left",
+            Controlls::TurnRight  => "turn_right",
+            Controlls::Strafe     => "strafe",
+            Controlls::Run        => "run",
+            Controlls::Use        => "use",
+            Controlls::Enter      => "fire",
+            Controlls::Escape     => "quit",
+            Controlls::Fullscreen => "fullscreen",
+        }
+    }
 
-    Spin(i32, i32), // (x, xrel)
+    pub fn from_name(name: &str) -> Option<Controlls> {
+        Controlls::ALL.into_iter().find(|control| control.name() == name)
+    }
 }
@@ -1,4 +1,10 @@
+use crate::components::projectile::Projectile;
+
 pub enum Event {
-    EnemyAttack(u32),
+    EnemyAttack(u32, f32, String), // damage, chance to hit and sound
+    Launch(Projectile, String), // and the sound of the shot
+    PlayerHit(u32),
+    EnemyHit(usize, i32), // enemy id and damage
+    Heal(u32),
     GameOver,
 }
\ No newline at end of file
@@ -0,0 +1,115 @@
+use std::{collections::HashMap, f32::consts::TAU};
+
+use crate::{
+    conf::{Config, SKY_REPEAT},
+    engine::TextureID,
+    headless::Image,
+    map::{map::{Map, Surface}, pos::Pos},
+    shading::{apply, fog, shade},
+};
+
+// textures a floor or ceiling can be drawn with
+pub const SURFACE_TEXTURES: [TextureID; 6] = [
+    TextureID::Wall1,
+    TextureID::Wall2,
+    TextureID::Wall3,
+    TextureID::Wall4,
+    TextureID::Wall5,
+    TextureID::Sky,
+];
+
+const CEILING_COLOR: [u8; 4] = [0, 0, 0, 255];
+
+// depth of the floor seen `row` pixels below the horizon, a wall one unit tall is drawn
+// screen_dist / depth pixels high, so the floor at that depth lands half of it under the horizon
+pub fn row_depth(row: u32, config: &Config) -> f32 {
+    0.5 * config.screen_dist() / (row as f32 + 0.5)
+}
+
+// plain floor color of every row below the horizon, fading into the fog
+pub fn floor_rows(config: &Config) -> Vec<[u8; 4]> {
+    let (r, g, b) = config.floor_color;
+
+    (0..config.height - config.half_height()).map(|row| {
+        apply([r, g, b, 255], shade(1.0, fog(row_depth(row, config), config)), config.fog_color)
+    }).collect()
+}
+
+// Floor and ceiling casting, every pixel below the horizon is traced back to the point of
+// the floor it shows and the ceiling mirrors it above. Both are lit like the walls
+pub fn cast(frame: &mut Image, textures: &HashMap<TextureID, Image>, map: &Map, (pos, angle): ((f32, f32), f32), config: &Config) {
+    let (width, height) = (frame.width(), frame.height());
+    let (half_width, half_height) = (config.half_width() as f32, config.half_height());
+    let screen_dist = config.screen_dist();
+
+    let (dir_x, dir_y) = (angle.cos(), angle.sin());
+    let (right_x, right_y) = (-dir_y, dir_x);
+
+    let (r, g, b) = config.floor_color;
+    let floor_color = [r, g, b, 255];
+
+    // the sky is infinitely far, it only depends on the direction of each column
+    let sky = textures.get(&TextureID::Sky).unwrap();
+
+    let sky_columns: Vec<u32> = (0..width).map(|x| {
+        let ray = angle + ((x as f32 - half_width) / screen_dist).atan();
+        let turn = (ray / TAU * SKY_REPEAT).rem_euclid(1.0);
+
+        ((turn * sky.width() as f32) as u32).min(sky.width() - 1)
+    }).collect();
+
+    for row in 0..height.saturating_sub(half_height) {
+        let depth = row_depth(row, config);
+        let fog = fog(depth, config);
+
+        // floor seen at the middle of the screen and how far it moves each pixel
+        let center = (pos.0 + dir_x * depth, pos.1 + dir_y * depth);
+        let step = (right_x * depth / screen_dist, right_y * depth / screen_dist);
+
+        let floor_y = half_height + row;
+        let ceiling_y = half_height.checked_sub(row + 1);
+
+        for x in 0..width {
+            let offset = x as f32 - half_width;
+            let (x_world, y_world) = (center.0 + step.0 * offset, center.1 + step.1 * offset);
+
+            let (tile_x, tile_y) = (x_world.floor(), y_world.floor());
+
+            let (floor, ceiling) = if tile_x >= 0.0 && tile_y >= 0.0 {
+                (map.floor(tile_x as usize, tile_y as usize), map.ceiling(tile_x as usize, tile_y as usize))
+            } else {
+                (Surface::Flat, Surface::Flat)
+            };
+
+            let (u, v) = (x_world - tile_x, y_world - tile_y);
+            let tint = shade(map.light(Pos::new(x_world, y_world)), fog);
+
+            let color = match floor {
+                Surface::Texture(texture_id) => apply(sample(textures, texture_id, u, v), tint, config.fog_color),
+                _                            => apply(floor_color, tint, config.fog_color),
+            };
+
+            frame.set_pixel(x, floor_y, color);
+
+            if let Some(y) = ceiling_y {
+                let color = match ceiling {
+                    Surface::Flat                => CEILING_COLOR,
+                    Surface::Texture(texture_id) => apply(sample(textures, texture_id, u, v), tint, config.fog_color),
+                    Surface::Sky                 => sky.pixel(sky_columns[x as usize], y * sky.height() / half_height),
+                };
+
+                frame.set_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+// u and v go from 0 to 1 across the tile
+fn sample(textures: &HashMap<TextureID, Image>, texture_id: TextureID, u: f32, v: f32) -> [u8; 4] {
+    let texture = textures.get(&texture_id).unwrap();
+
+    let x = ((u * texture.width() as f32) as u32).min(texture.width() - 1);
+    let y = ((v * texture.height() as f32) as u32).min(texture.height() - 1);
+
+    texture.pixel(x, y)
+}
@@ -1,106 +1,270 @@
 use std::{
-    cmp::{max, min},
+    collections::HashMap,
+    error::Error,
     ops::Not,
+    rc::Rc,
 };
 
 use crate::{
-    component::Component, components::{
-        background::Background, enemy::Enemy, player::Player, weapon::{Weapon, RELOAD_TEXTURES, SHOOTING_TEXTURES}
+    assets::npc_frames, component::Component, components::{
+        background::Background, decoration::{decoration_textures, Decoration}, enemy::Enemy, pickup::{pickup_textures, Pickup}, player::Player, projectile::{projectile_textures, Projectile}, weapon::{Weapon, RELOAD_TEXTURES, SHOOTING_TEXTURES}
     }, conf::{
-        HALF_HEIGHT, HALF_WIDTH, MOUSE_BORDER_LEFT, MOUSE_BORDER_RIGHT, MOUSE_MAX_REL,
-        MOUSE_SENSITIVITY,
-    }, engine::{Controlls, Driver, Sprite}, events::Event, map::mov::Mov, sequence::EnemySequence, soldier::{SOLDIER_DYING, SOLDIER_PAIN, SOLDIER_SHOTING, SOLDIER_WALKING}
+        Config, HEARING_RANGE, MAX_FRAME_TIME, MOUSE_BORDER_LEFT, MOUSE_MAX_REL, PLAYER_ROT_SPEED, TICK,
+    }, demo::{Demo, Divergence, Playback, CHECKSUM_INTERVAL}, enemy_kinds::EnemyKinds, engine::{Controlls, Driver, Sprite}, events::Event, input::{dead_zone, Axis}, map::{flow::FlowField, map::Map, mov::Mov, path::PathFinding, pos::Pos, raycasting::line_of_sight, spawn::Spawn}, observer::{EnemyState, PlayerState, WorldState}, rng::Rng, sequence::{EnemySequence, ROTATIONS}, spatial::{Actor, SpatialHash}
 };
 
-const DELTA_TIME: f32 = 60.0;
+const RNG_SEED: u32 = 0x5eed;
 
 pub struct Game<'a> {
     engine: Box<dyn Driver + 'a>,
+    config: Config, // the only copy, handed to whatever needs it
 
-    background: Background,
     player: Player,
     enemies: Vec<Enemy>,
+    decorations: Vec<Decoration>,
+    pickups: Vec<Pickup>,
+    projectiles: Vec<Projectile>,
     weapon: Weapon,
+
+    navigation: FlowField, // towards the player, shared by every enemy
+    paths: PathFinding, // enemies keep their own route, the map is searched through this one
+    actors: SpatialHash, // living enemies, to find the ones around a point
+
+    // state at the start of the last tick, rendering interpolates from it
+    previous_player: Player,
+    previous_enemies: Vec<Pos>,
+
     is_over: bool,
+
+    tick: u32,
+    rng: Rng, // fixed seed, so demos replay the same shots
+    recording: Option<Demo>,
+    playback: Option<Playback>,
 }
 
 impl<'a> Game<'a> {
-    pub fn new( engine: Box<dyn Driver + 'a>) -> Box<Self> {
-        let background = Background {};
-        
-        let player = Player::new_player();
+    // fails when the map has an enemy of a kind that isn't defined
+    pub fn new(mut engine: Box<dyn Driver + 'a>, config: Config, map: Map) -> Result<Box<Self>, Box<dyn Error>> {
+        let map = Rc::new(map);
+
+        engine.set_map(map.clone());
+
+        let player = Player::new_player(map.clone());
 
         let weapon = Weapon::new(
             engine.load_refs(&SHOOTING_TEXTURES), 
             engine.load_refs(&RELOAD_TEXTURES),
         );
 
-        let (shift, scale) = (0.27, 0.7);
-
-        let soldier_sequences = EnemySequence::new(
-            engine.load_refs(&SOLDIER_WALKING),
-            engine.load_refs(&SOLDIER_SHOTING),
-            engine.load_refs(&SOLDIER_PAIN),
-            engine.load_refs(&SOLDIER_DYING),
-        );
-
-        let cloned_sequence = soldier_sequences.clone()
-
-        // O bien
-
-        // let mut enemies = Vec::new() !!importante el mut pars poder añadirle elementos
-
-        // enemies.push(Enemy::new(100, 5, (10.5, 3.5), soldier_sequences, shift, scale))
-        // ...
+        let kinds = EnemyKinds::bundled()?;
+        let mut sequences = HashMap::new();
+
+        let mut enemies = Vec::new();
+        let mut decorations = Vec::new();
+        let mut pickups = Vec::new();
+
+        // everything else in the level comes from the map file
+        for spawn in map.spawns() {
+            match *spawn {
+                Spawn::Player(..) => {}
+                Spawn::Enemy(ref kind, pos) => {
+                    let definition = kinds.get(kind).ok_or_else(|| format!("unknown enemy {:?}", kind))?;
+
+                    let sequences = sequences.entry(kind.clone())
+                        .or_insert_with(|| enemy_sequences(engine.as_ref(), kind, definition.attack_time))
+                        .clone();
+
+                    let projectile = definition.projectile.as_deref()
+                        .and_then(projectile_textures)
+                        .map(|textures| engine.load_refs(textures))
+                        .unwrap_or_default();
+
+                    enemies.push(Enemy::new(enemies.len(), definition, (pos.x(), pos.y()), sequences, projectile));
+                }
+                Spawn::Decoration(kind, pos) => {
+                    let textures = engine.load_refs(decoration_textures(kind));
+
+                    decorations.push(Decoration::new(kind, pos, textures));
+                }
+                Spawn::Pickup(kind, pos) => {
+                    let textures = engine.load_refs(pickup_textures(kind));
+
+                    pickups.push(Pickup::new(kind, pos, textures));
+                }
+            }
+        }
 
-        let enemies = vec![
-            Enemy::new(100, 5, (10.5, 3.5), soldier_sequences, shift, scale),
-            Enemy::new(100, 5, (12.5, 4.5), cloned_sequence, shift, scale),
-        ];
+        let previous_enemies = enemies.iter().map(Enemy::pos).collect();
 
-        Box::new(Self {
+        Ok(Box::new(Self {
             engine,
+            config,
+            previous_player: player.clone(),
             player,
+            previous_enemies,
             enemies,
+            decorations,
+            pickups,
+            projectiles: Vec::new(),
             weapon,
+            navigation: FlowField::new(&map),
+            paths: PathFinding::new(&map),
+            actors: SpatialHash::default(),
             is_over: false,
-            background,
-        })
+            tick: 0,
+            rng: Rng::new(RNG_SEED),
+            recording: None,
+            playback: None,
+        }))
     }
 
+    // the world is simulated in fixed steps of TICK seconds as real time accumulates,
+    // frames are rendered as fast as the driver allows interpolating between steps
     pub fn run(&mut self) {
-        'running: loop {
-            if let Some(_exit) = self.handle_controls() {
-                break 'running;
+        let mut accumulator = 0.0;
+
+        loop {
+            accumulator += self.engine.delta_time().min(MAX_FRAME_TIME);
+
+            while accumulator >= TICK {
+                if !self.tick() {
+                    return;
+                }
+
+                accumulator -= TICK;
             }
 
-            let events = self.update();
+            self.render(accumulator / TICK);
+        }
+    }
+
+    // advances the world a single step without rendering,
+    // returns false once the player asks to leave the game
+    pub fn tick(&mut self) -> bool {
+        if self.playback.as_ref().is_some_and(|playback| playback.finished(self.tick)) {
+            return false;
+        }
+
+        self.previous_player = self.player.clone();
+        self.previous_enemies = self.enemies.iter().map(Enemy::pos).collect();
 
-            self.handle_events(events);
+        self.locate_enemies();
 
-            self.render();
+        if let Some(_exit) = self.handle_controls() {
+            return false;
         }
+
+        let events = self.update();
+
+        self.handle_events(events);
+
+        self.checkpoint();
+
+        self.tick += 1;
+
+        true
     }
 
-    // TODO: ejercicio: escribir el cuerpo de esto
-    fn render(&mut self) {
-        let mut components: Vec<Box<dyn Component>> = Vec::new()
+    // runs up to `ticks` steps, returns how many were run before the player left
+    pub fn run_ticks(&mut self, ticks: u32) -> u32 {
+        (0..ticks).take_while(|_| self.tick()).count() as u32
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Demo::new(self.player.map(), &self.config));
+    }
+
+    pub fn stop_recording(&mut self) -> Option<Demo> {
+        let mut demo = self.recording.take()?;
+
+        demo.finish(self.tick);
+
+        Some(demo)
+    }
+
+    // input comes from the demo instead of the driver from now on, played with the
+    // settings it was recorded with. Demos of another map or window width are refused,
+    // the world must be in the same state it was when the demo was recorded
+    pub fn play(&mut self, demo: Demo) -> Result<(), Box<dyn Error>> {
+        self.config = demo.configure(self.player.map(), &self.config)?;
+        self.playback = Some(Playback::new(demo));
+
+        Ok(())
+    }
+
+    pub fn divergence(&self) -> Option<Divergence> {
+        self.playback.as_ref().and_then(Playback::divergence)
+    }
+
+    fn checkpoint(&mut self) {
+        if !self.tick.is_multiple_of(CHECKSUM_INTERVAL) {
+            return;
+        }
+
+        let checksum = self.observe().checksum();
+
+        if let Some(demo) = self.recording.as_mut() {
+            demo.record_checksum(self.tick, checksum);
+        }
+
+        if let Some(playback) = self.playback.as_mut() {
+            playback.check(self.tick, checksum);
+        }
+    }
+
+    pub fn observe(&self) -> WorldState {
+        WorldState {
+            player: PlayerState {
+                life: self.player.life(),
+                pos: (self.player.pos().x(), self.player.pos().y()),
+                angle: self.player.angle(),
+            },
+            enemies: self.enemies.iter().map(|enemy| EnemyState {
+                life: enemy.life(),
+                pos: (enemy.pos().x(), enemy.pos().y()),
+                status: enemy.status(),
+                ai: enemy.ai_state(),
+            }).collect(),
+            projectiles: self.projectiles.iter()
+                .map(|projectile| (projectile.pos().x(), projectile.pos().y()))
+                .collect(),
+            is_over: self.is_over,
+        }
+    }
+
+    fn render(&mut self, alpha: f32) {
+        let (width, height) = self.engine.screen_size();
+
+        // the projection of everything on screen depends on the window size
+        if (width, height) != (self.config.width, self.config.height) {
+            self.config.resize(width, height);
+        }
+
+        let mut components: Vec<Box<dyn Component>> = Vec::new();
+
+        let player = self.player.interpolate(&self.previous_player, alpha);
 
         // añade componentes: self, background, armas y enemigos)
-        components.push(Box::new(self.player));
-        components.push(Box::new(self.background));
-        components.push(Box::new(self.weapons));
-        components.extend(self.enemies.iter()
-            .map(|enemy| Box::new(enemy.clone()) as Box<dyn Component>));
+        components.push(Box::new(Background::new(&player)));
+        components.push(Box::new(self.weapon.clone()));
+        components.extend(self.enemies.iter().zip(&self.previous_enemies)
+            .map(|(enemy, previous)| Box::new(enemy.interpolate(*previous, &player, alpha, &self.config)) as Box<dyn Component>));
+        components.extend(self.decorations.iter()
+            .map(|decoration| Box::new(decoration.project(&player, &self.config)) as Box<dyn Component>));
+        components.extend(self.pickups.iter()
+            .map(|pickup| Box::new(pickup.project(&player, &self.config)) as Box<dyn Component>));
+        components.extend(self.projectiles.iter()
+            .map(|projectile| Box::new(projectile.project(&player, alpha, &self.config)) as Box<dyn Component>));
+        components.push(Box::new(player));
 
         // se crea un array de sprites con todos los componentes
-        let sprites: Vec<Sprite>: components.iter()
-            .filter(|component| component.visible())
-            .flat_map(|component| component.get_sprites())
-            .collect()
+        let sprites: Vec<Sprite> = components.iter()
+            .filter(|component| component.visible(&self.config))
+            .flat_map(|component| component.get_sprites(&self.config))
+            .collect();
 
         // llama al metodo render
-        self.engine.render(sprites)
+        self.engine.render(sprites);
     }
 
     fn update(&mut self) -> Vec<Event> {
@@ -108,26 +272,73 @@ impl<'a> Game<'a> {
 
         self.player.alive().not().then(|| events.push(Event::GameOver));
 
-        let enemies = self.enemies.clone();
+        self.navigation.update(self.player.pos().map());
+        self.navigation.occupy(self.enemies.iter().map(|enemy| enemy.pos().map()));
 
         self.enemies
             .iter_mut()
-            .map(|soldier| soldier.update(&self.player, enemies.clone()))
-            .flatten()
+            .filter_map(|enemy| enemy.act(&self.player, &self.navigation, &self.paths, &self.actors, &self.config))
             .for_each(|event| events.push(event));
 
-        self.weapon.update(&self.player, self.enemies.clone());
-        self.player.update(&self.player.clone(), self.enemies.clone());
+        // where they ended up, for the projectiles
+        self.locate_enemies();
+
+        self.decorations.iter_mut().for_each(|decoration| {
+            decoration.update(&self.player, &self.actors);
+        });
+
+        self.pickups
+            .iter_mut()
+            .filter_map(|pickup| pickup.update(&self.player, &self.actors))
+            .for_each(|event| events.push(event));
+
+        self.pickups.retain(|pickup| !pickup.taken());
+
+        self.projectiles
+            .iter_mut()
+            .filter_map(|projectile| projectile.update(&self.player, &self.actors))
+            .for_each(|event| events.push(event));
+
+        self.projectiles.retain(|projectile| !projectile.exploded());
+
+        self.weapon.update(&self.player, &self.actors);
+        self.player.recover();
 
         events
     }
 
+    // corpses don't get in the way
+    fn locate_enemies(&mut self) {
+        self.actors.clear();
+
+        self.enemies.iter()
+            .filter(|enemy| enemy.alive())
+            .for_each(|enemy| self.actors.insert(Actor { id: enemy.id(), pos: enemy.pos(), radius: enemy.kind().radius }));
+    }
+
     fn handle_events(&mut self, events: Vec<Event>) {
-        events.iter().for_each(|event| match event {
-            Event::EnemyAttack(damage) => {
-                self.engine.play_enemy_shoot();
+        events.into_iter().for_each(|event| match event {
+            Event::EnemyAttack(damage, chance, sound) => {
+                self.engine.play_sound(&sound);
+
+                if self.rng.next_f32() < chance {
+                    self.engine.play_player_pain();
+                    self.player.hit(damage);
+                }
+            }
+            Event::Launch(projectile, sound) => {
+                self.engine.play_sound(&sound);
+                self.projectiles.push(projectile);
+            }
+            Event::PlayerHit(damage) => {
                 self.engine.play_player_pain();
-                self.player.hit(*damage);
+                self.player.hit(damage);
+            }
+            Event::EnemyHit(id, damage) => {
+                self.damage_enemy(id, damage);
+            }
+            Event::Heal(life) => {
+                self.player.heal(life);
             }
             Event::GameOver => {
                 self.is_over = true;
@@ -136,42 +347,138 @@ impl<'a> Game<'a> {
     }
 
     fn handle_controls(&mut self) -> Option<()> {
-        match self.engine.poll() {
-            Some(control) => match control {
-                Controlls::Escape        => return Some(()), // shutdowns the game
-                Controlls::ArrowUp       => self.player.walk(Mov::North, DELTA_TIME),
-                Controlls::ArrowDown     => self.player.walk(Mov::South, DELTA_TIME),
-                Controlls::ArrowLeft     => self.player.walk(Mov::West, DELTA_TIME),
-                Controlls::ArrowRight    => self.player.walk(Mov::East, DELTA_TIME),
-                Controlls::Spin(x, xrel) => self.spin_camera(x, xrel),
-                Controlls::Enter         => self.shoot(),
-            },
-            None => {}
+        let input = self.engine.poll();
+
+        // live input is still drained while replaying so the driver keeps responding
+        let input = match self.playback.as_mut() {
+            Some(playback) => playback.poll(self.tick),
+            None => input,
+        };
+
+        if let Some(demo) = self.recording.as_mut() {
+            demo.record_input(self.tick, &input);
+        }
+
+        if input.pressed(Controlls::Escape) {
+            return Some(()); // shutdowns the game
+        }
+
+        // with strafe held the turn controls slide sideways instead
+        let strafing = input.held(Controlls::Strafe);
+
+        let mut moves = vec![
+            (Controlls::ArrowUp,    Mov::North),
+            (Controlls::ArrowDown,  Mov::South),
+            (Controlls::ArrowLeft,  Mov::West),
+            (Controlls::ArrowRight, Mov::East),
+        ];
+
+        if strafing {
+            moves.extend([(Controlls::TurnLeft, Mov::West), (Controlls::TurnRight, Mov::East)]);
+        }
+
+        let (forward, strafe) = moves.into_iter()
+            .filter(|(control, _)| input.held(*control))
+            .map(|(_, mov)| mov.direction())
+            .fold((0.0, 0.0), |(f, s), (df, ds)| (f + df, s + ds));
+
+        let forward = forward + dead_zone(input.axis(Axis::Forward), self.config.stick_dead_zone) * self.config.stick_move_sensitivity;
+        let strafe = strafe + dead_zone(input.axis(Axis::Strafe), self.config.stick_dead_zone) * self.config.stick_move_sensitivity;
+
+        self.player.walk(forward, strafe, input.held(Controlls::Run), TICK, &self.actors, &self.config);
+
+        let mut turn = dead_zone(input.axis(Axis::Turn), self.config.stick_dead_zone) * self.config.stick_turn_sensitivity;
+
+        if !strafing {
+            let keys = input.held(Controlls::TurnRight) as i32 - input.held(Controlls::TurnLeft) as i32;
+
+            turn += keys as f32 * PLAYER_ROT_SPEED;
+        }
+
+        self.player.spin(turn * TICK);
+
+        if input.mouse_dx() != 0 {
+            self.spin_camera(input.mouse_x(), input.mouse_dx());
+        }
+
+        if input.pressed(Controlls::Enter) {
+            self.shoot();
+        }
+
+        if input.pressed(Controlls::Fullscreen) {
+            self.engine.toggle_fullscreen();
         }
 
         None
     }
 
     fn spin_camera(&mut self, x: i32, xrel: i32) {
-        if x < MOUSE_BORDER_LEFT || x > MOUSE_BORDER_RIGHT {
-            self.engine.center_mouse(HALF_WIDTH as i32, HALF_HEIGHT as i32);
+        if x < MOUSE_BORDER_LEFT || x > self.config.mouse_border_right() {
+            self.engine.center_mouse(self.config.half_width() as i32, self.config.half_height() as i32);
         }
 
-        let rel = max(-MOUSE_MAX_REL, min(MOUSE_MAX_REL, xrel));
+        let rel = xrel.clamp(-MOUSE_MAX_REL, MOUSE_MAX_REL);
 
-        self.player.spin(rel as f32 * MOUSE_SENSITIVITY * DELTA_TIME);
+        self.player.spin(rel as f32 * self.config.mouse_sensitivity);
     }
 
     fn shoot(&mut self) {
         self.weapon.shoot();
         self.engine.play_shoot();
 
-        for enemy in self.enemies.iter_mut() {
-            if enemy.alive() && enemy.hit(&self.player) {
-                self.engine.play_pain_shout();
+        let player = self.player.pos();
 
-                enemy.receive_damage(self.weapon.damage());
-            }
+        self.enemies.iter_mut()
+            .filter(|enemy| {
+                let d = enemy.pos() - player;
+
+                d.x().hypot(d.y()) <= HEARING_RANGE
+            })
+            .for_each(Enemy::hear);
+
+        // the shot stops at the first living enemy along the crosshair that isn't behind a wall
+        self.locate_enemies();
+
+        let target = self.actors.along(player, self.player.angle(), self.config.max_depth as f32).into_iter()
+            .map(|(actor, _)| actor)
+            .find(|actor| line_of_sight(self.player.map(), player, actor.pos));
+
+        if let Some(actor) = target {
+            self.damage_enemy(actor.id, self.weapon.damage());
+        }
+    }
+
+    fn damage_enemy(&mut self, id: usize, damage: i32) {
+        let enemy = &mut self.enemies[id];
+
+        if !enemy.alive() {
+            return;
         }
+
+        enemy.receive_damage(damage);
+
+        let kind = enemy.kind();
+
+        self.engine.play_sound(if enemy.alive() { &kind.pain_sound } else { &kind.death_sound });
     }
-}
\ No newline at end of file
+}
+
+// eight idle frames show the enemy from every side, it only walks towards the camera
+fn enemy_sequences(engine: &dyn Driver, kind: &str, attack_time: f32) -> EnemySequence {
+    let idle = npc_frames(kind, "idle");
+
+    let idle = if idle.len() == ROTATIONS {
+        idle.iter().map(|id| engine.load_refs(&[*id])).collect()
+    } else {
+        vec![engine.load_refs(&idle)]
+    };
+
+    EnemySequence::new(
+        idle,
+        vec![engine.load_refs(&npc_frames(kind, "walk"))],
+        engine.load_refs(&npc_frames(kind, "attack")),
+        engine.load_refs(&npc_frames(kind, "pain")),
+        engine.load_refs(&npc_frames(kind, "death")),
+        attack_time,
+    )
+}
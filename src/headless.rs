@@ -0,0 +1,313 @@
+use std::{collections::HashMap, error::Error, fs::File, path::Path, rc::Rc};
+
+use crate::{assets::all_textures, conf::{Config, TEXTURE_SIZE}, engine::{digit_to_texture, span_source, DepthBuffer, Driver, Sprite, TextureID}, floor::{self, floor_rows}, input::InputState, map::map::Map, shading::{apply, Shade, FULL_LIGHT}, texture::TextureRef};
+
+// RGBA image with 8 bits per channel, used both for textures and the framebuffer
+#[derive(Clone)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    pub fn from_png(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut decoder = png::Decoder::new(File::open(path)?);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+
+        let data = &buffer[..info.buffer_size()];
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => data.to_vec(),
+            png::ColorType::Rgb => data.chunks(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+            png::ColorType::GrayscaleAlpha => data.chunks(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+            png::ColorType::Grayscale => data.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+            png::ColorType::Indexed => return Err("unexpanded indexed png".into()),
+        };
+
+        Ok(Image { width: info.width, height: info.height, pixels })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * self.width + x) * 4) as usize;
+
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    // overwrites the pixel, alpha included
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        let i = ((y * self.width + x) * 4) as usize;
+
+        self.pixels[i..i + 4].copy_from_slice(&color);
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        let i = ((y * self.width + x) * 4) as usize;
+        let alpha = color[3] as u32;
+
+        for (dst, &src) in self.pixels[i..i + 3].iter_mut().zip(color.iter()) {
+            *dst = ((src as u32 * alpha + *dst as u32 * (255 - alpha)) / 255) as u8;
+        }
+
+        self.pixels[i + 3] = 255;
+    }
+}
+
+// decodes every texture the game uses, the paths are resolved against `root`
+// instead of the working directory so tests can load them from the crate folder
+pub fn load_textures(root: &Path) -> Result<HashMap<TextureID, Image>, Box<dyn Error>> {
+    let ids: Vec<TextureID> = all_textures().map(|(texture_id, _)| texture_id).collect();
+
+    load_some_textures(root, &ids)
+}
+
+pub fn load_some_textures(root: &Path, ids: &[TextureID]) -> Result<HashMap<TextureID, Image>, Box<dyn Error>> {
+    all_textures().filter(|(texture_id, _)| ids.contains(texture_id)).map(|(texture_id, path)| {
+        let image = Image::from_png(&root.join(path.trim_start_matches("../")))?;
+
+        Ok((texture_id, image))
+    }).collect()
+}
+
+// (x, y, width, height), same convention as sdl2::rect::Rect
+type Area = (i32, i32, u32, u32);
+
+// Software rasterizer with no window, audio device or GPU: draws the sprite
+// commands into an in-memory RGBA framebuffer that can be inspected after render
+pub struct HeadlessEngine {
+    frame: Image,
+    textures: HashMap<TextureID, Image>,
+    config: Config,
+    map: Option<Rc<Map>>,
+}
+
+impl HeadlessEngine {
+    pub fn new(textures: HashMap<TextureID, Image>, config: Config) -> Box<Self> {
+        Box::new(Self {
+            frame: Image::new(config.width, config.height),
+            textures,
+            config,
+            map: None,
+        })
+    }
+
+    pub fn frame(&self) -> &Image {
+        &self.frame
+    }
+
+    fn fill(&mut self, (x, y, w, h): Area, color: [u8; 4]) {
+        let (x0, x1) = (x.max(0), (x + w as i32).min(self.frame.width as i32));
+        let (y0, y1) = (y.max(0), (y + h as i32).min(self.frame.height as i32));
+
+        for py in y0..y1 {
+            for px in x0..x1 {
+                self.frame.blend(px as u32, py as u32, color);
+            }
+        }
+    }
+
+    // nearest neighbour scaled copy, the source area is clipped to the texture
+    // but the destination is kept as is, mirroring SDL_RenderCopy with a color mod
+    fn copy(&mut self, texture_id: TextureID, src: Option<Area>, (dx, dy, dw, dh): Area, shade: Shade) {
+        let texture = self.textures.get(&texture_id).unwrap();
+
+        let (sx, sy, sw, sh) = match src {
+            None => (0, 0, texture.width, texture.height),
+            Some((x, y, w, h)) => {
+                let x0 = x.clamp(0, texture.width as i32);
+                let y0 = y.clamp(0, texture.height as i32);
+                let x1 = (x + w as i32).clamp(0, texture.width as i32);
+                let y1 = (y + h as i32).clamp(0, texture.height as i32);
+
+                (x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32)
+            }
+        };
+
+        if sw == 0 || sh == 0 || dw == 0 || dh == 0 {
+            return;
+        }
+
+        let (x0, x1) = (dx.max(0), (dx as i64 + dw as i64).min(self.frame.width as i64) as i32);
+        let (y0, y1) = (dy.max(0), (dy as i64 + dh as i64).min(self.frame.height as i64) as i32);
+
+        for py in y0..y1 {
+            let ty = sy + ((py - dy) as u64 * sh as u64 / dh as u64) as u32;
+
+            for px in x0..x1 {
+                let tx = sx + ((px - dx) as u64 * sw as u64 / dw as u64) as u32;
+                let color = apply(texture.pixel(tx, ty), shade, self.config.fog_color);
+
+                self.frame.blend(px as u32, py as u32, color);
+            }
+        }
+    }
+
+    fn render_background(&mut self, camera: ((f32, f32), f32)) {
+        if let Some(map) = self.map.as_ref().filter(|map| map.has_surfaces()) {
+            floor::cast(&mut self.frame, &self.textures, map, camera, &self.config);
+            return;
+        }
+
+        let (width, half_height) = (self.config.width, self.config.half_height());
+
+        self.fill((0, 0, width, half_height), [0, 0, 0, 255]);
+
+        for (row, color) in floor_rows(&self.config).into_iter().enumerate() {
+            self.fill((0, (half_height as usize + row) as i32, width, 1), color);
+        }
+    }
+
+    fn render_column(&mut self, texture_id: TextureID, num: usize, offset: f32, proj_height: f32, shade: Shade) {
+        // same factor the sdl driver applies through ScaledTexture
+        let factor   = self.texture_size(texture_id).0 as f32 / TEXTURE_SIZE as f32;
+        let scale    = self.config.scale();
+        let origin   = (offset * (TEXTURE_SIZE - scale) as f32 * factor) as i32;
+
+        let portion  = (origin, 0, scale, TEXTURE_SIZE * 15);
+        let location = ((num * (scale as usize)) as i32,
+                        self.config.half_height() as i32 - (proj_height / 2.0) as i32,
+                        scale,
+                        proj_height as u32);
+
+        self.copy(texture_id, Some(portion), location, shade);
+    }
+
+    fn texture_size(&self, texture_id: TextureID) -> (u32, u32) {
+        let texture = self.textures.get(&texture_id).unwrap();
+
+        (texture.width, texture.height)
+    }
+
+    // texture size once scaled to the window height, for the HUD
+    fn hud_size(&self, texture_id: TextureID) -> (u32, u32) {
+        let (width, height) = self.texture_size(texture_id);
+        let scale = self.config.hud_scale();
+
+        ((width as f32 * scale) as u32, (height as f32 * scale) as u32)
+    }
+}
+
+impl Driver for HeadlessEngine {
+    // there is no real clock to wait on, each frame takes exactly 1 / fps seconds
+    fn delta_time(&mut self) -> f32 {
+        1.0 / self.config.fps as f32
+    }
+
+    fn poll(&mut self) -> InputState {
+        InputState::new()
+    }
+
+    fn play_shoot(&mut self) {}
+    fn play_sound(&mut self, _: &str) {}
+    fn play_player_pain(&mut self) {}
+
+    fn center_mouse(&self, _: i32, _: i32) {}
+
+    fn screen_size(&self) -> (u32, u32) {
+        (self.frame.width, self.frame.height)
+    }
+
+    fn toggle_fullscreen(&mut self) {}
+
+    fn set_map(&mut self, map: Rc<Map>) {
+        self.map = Some(map);
+    }
+
+    fn get_texture_width(&self, texture_id: TextureID) -> u32 {
+        self.texture_size(texture_id).0
+    }
+
+    fn get_image_ratio(&self, texture_id: TextureID) -> f32 {
+        let (width, height) = self.texture_size(texture_id);
+
+        width as f32 / height as f32
+    }
+
+    fn load_refs(&self, ids: &[TextureID]) -> Vec<TextureRef> {
+        ids.iter().map(|id| {
+            let width = self.get_texture_width(*id);
+            let ratio = self.get_image_ratio(*id);
+
+            TextureRef::new(*id, width, ratio)
+        }).collect()
+    }
+
+    fn render(&mut self, mut commands: Vec<Sprite>) {
+        commands.sort_by(|a, b| b.z_order().partial_cmp(&a.z_order()).unwrap());
+
+        let depth_buffer = DepthBuffer::new(&commands, self.config.width, self.config.scale());
+
+        commands.into_iter().for_each(|command| {
+            match command {
+                Sprite::Background(pos, angle) => self.render_background((pos, angle)),
+                Sprite::Column(_, texture_id, num, offset, proj_height, shade) => {
+                    self.render_column(texture_id, num, offset, proj_height, shade);
+                }
+                Sprite::Sprite(depth, texture_id, pos, proj_width, proj_height, shade) => {
+                    let (x, width) = (pos.0 as i32, proj_width as u32);
+                    let (texture_width, texture_height) = self.texture_size(texture_id);
+
+                    for span in depth_buffer.visible_spans(x, width, depth) {
+                        let (source_x, source_width) = span_source(span, x, width, texture_width);
+
+                        let portion  = (source_x, 0, source_width, texture_height);
+                        let location = (span.0, pos.1 as i32, (span.1 - span.0) as u32, proj_height as u32);
+
+                        self.copy(texture_id, Some(portion), location, shade);
+                    }
+                }
+                Sprite::DrawWeapon(texture_id) => {
+                    let (width, height) = self.hud_size(texture_id);
+
+                    let location = (self.config.half_width() as i32 - (width / 4) as i32,
+                                    self.config.height as i32 - (height / 2) as i32,
+                                    width / 2,
+                                    height / 2);
+
+                    self.copy(texture_id, None, location, FULL_LIGHT);
+                }
+                Sprite::PainScreen => {
+                    self.copy(TextureID::PainScreen, None, (0, 0, self.config.width, self.config.height), FULL_LIGHT);
+                }
+                Sprite::LifeCounter(life) => {
+                    let counter = life.to_string();
+
+                    for (i, digit) in counter.char_indices() {
+                        let texture_id = digit_to_texture(digit);
+
+                        let (width, height) = self.hud_size(texture_id);
+
+                        self.copy(texture_id, None, ((i as u32 * width) as i32, 0, width, height), FULL_LIGHT);
+                    }
+                }
+                Sprite::GameOver => {
+                    self.copy(TextureID::GameOver, None, (0, 0, self.config.width, self.config.height), FULL_LIGHT);
+                }
+            }
+        });
+    }
+}
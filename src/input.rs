@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+
+use crate::engine::Controlls;
+
+// analog controls, values go from -1 to 1
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Axis {
+    Forward,
+    Strafe, // positive to the right
+    Turn,   // positive to the right
+}
+
+// State of the controls since the previous poll: which ones are held down,
+// which were pressed or released in between, how far the mouse moved
+// and where the analog sticks are
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct InputState {
+    held: HashSet<Controlls>,
+    pressed: HashSet<Controlls>,
+    released: HashSet<Controlls>,
+
+    mouse_x: i32,
+    mouse_dx: i32,
+
+    axes: [f32; 3],
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // controls held down with no motion, handy to write scripts
+    pub fn holding(controls: &[Controlls]) -> Self {
+        InputState {
+            held: controls.iter().copied().collect(),
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn from_parts(
+        held: &[Controlls],
+        pressed: &[Controlls],
+        released: &[Controlls],
+        (mouse_x, mouse_dx): (i32, i32),
+        axes: [f32; 3],
+    ) -> Self {
+        InputState {
+            held: held.iter().copied().collect(),
+            pressed: pressed.iter().copied().collect(),
+            released: released.iter().copied().collect(),
+            mouse_x,
+            mouse_dx,
+            axes,
+        }
+    }
+
+    pub fn with_mouse(mut self, x: i32, dx: i32) -> Self {
+        self.move_mouse(x, dx);
+        self
+    }
+
+    pub fn with_axis(mut self, axis: Axis, value: f32) -> Self {
+        self.set_axis(axis, value);
+        self
+    }
+
+    pub fn held(&self, control: Controlls) -> bool {
+        self.held.contains(&control)
+    }
+
+    pub fn pressed(&self, control: Controlls) -> bool {
+        self.pressed.contains(&control)
+    }
+
+    pub fn released(&self, control: Controlls) -> bool {
+        self.released.contains(&control)
+    }
+
+    pub fn mouse_x(&self) -> i32 {
+        self.mouse_x
+    }
+
+    pub fn mouse_dx(&self) -> i32 {
+        self.mouse_dx
+    }
+
+    // raw stick position, see `dead_zone`
+    pub fn axis(&self, axis: Axis) -> f32 {
+        self.axes[axis as usize]
+    }
+
+    pub fn axes(&self) -> [f32; 3] {
+        self.axes
+    }
+
+    // nothing was pressed, released or moved since the previous poll
+    pub fn is_steady(&self) -> bool {
+        self.pressed.is_empty() && self.released.is_empty() && self.mouse_dx == 0
+    }
+
+    pub fn press(&mut self, control: Controlls) {
+        self.held.insert(control);
+        self.pressed.insert(control);
+    }
+
+    pub fn release(&mut self, control: Controlls) {
+        self.held.remove(&control);
+        self.released.insert(control);
+    }
+
+    pub fn move_mouse(&mut self, x: i32, dx: i32) {
+        self.mouse_x = x;
+        self.mouse_dx += dx;
+    }
+
+    pub fn set_axis(&mut self, axis: Axis, value: f32) {
+        self.axes[axis as usize] = value.clamp(-1.0, 1.0);
+    }
+
+    // starts a new poll, held controls and sticks stay where they are
+    pub fn clear_edges(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.mouse_dx = 0;
+    }
+
+    // moves to the held controls and mouse motion of `next`, deriving the edges in between
+    pub fn follow(&mut self, next: &InputState) {
+        self.clear_edges();
+
+        for control in Controlls::ALL {
+            match (self.held(control), next.held(control)) {
+                (false, true) => self.press(control),
+                (true, false) => self.release(control),
+                _ => {}
+            }
+        }
+
+        self.move_mouse(next.mouse_x, next.mouse_dx);
+        self.axes = next.axes;
+    }
+
+    // same state with the edges dropped, what the next poll looks like with no new events
+    pub fn steady(&self) -> InputState {
+        let mut state = self.clone();
+        state.clear_edges();
+        state
+    }
+}
+
+// sticks rest a bit off center, anything inside the dead zone counts as centered
+// and the rest of the range is stretched back to go from 0 to 1
+pub fn dead_zone(value: f32, zone: f32) -> f32 {
+    if value.abs() < zone {
+        0.0
+    } else {
+        value.signum() * (value.abs() - zone) / (1.0 - zone)
+    }
+}
@@ -1,29 +1,47 @@
 mod component;
-mod engine;
 mod events;
+mod rng;
 mod sequence;
-mod soldier;
-mod texture;
 
 // deben ser publicos los que se usan en el main
+pub mod ai;
 pub mod assets;
+pub mod bindings;
 pub mod conf;
+pub mod demo;
+pub mod engine;
+pub mod enemy_kinds;
+pub mod floor;
 pub mod game;
+pub mod input;
+pub mod observer;
+pub mod script;
+pub mod shading;
+pub mod spatial;
+pub mod texture;
 
+pub mod headless;
 pub mod sdl;
 
-mod map {
+#[allow(clippy::module_inception)]
+pub mod map {
+    pub mod collision;
+    pub mod flow;
     pub mod map;
-    pub mod mov;
     pub mod path;
     pub mod pos;
     pub mod raycasting;
-    mod traverse;
+    pub(crate) mod mov;
+    pub mod spawn;
 }
 
 mod components {
+    pub mod billboard;
+    pub mod decoration;
+    pub mod pickup;
     pub mod background;
     pub mod enemy;
     pub mod player;
+    pub mod projectile;
     pub mod weapon;
 }
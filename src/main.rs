@@ -1,37 +1,111 @@
-use std::collections::HashMap;
+use std::env;
 use std::error::Error;
-use std::path::Path;
+use std::path::PathBuf;
 
-use doomie::assets::TEXTURES_PATHS;
-use doomie::conf::{FPS, HEIGHT, WIDTH};
-use doomie::sdl::{SDLengine, ScaledTexture};
+use doomie::bindings::Bindings;
+use doomie::conf::Config;
+use doomie::demo::Demo;
+use doomie::map::map::Map;
+use doomie::sdl::{KeyMap, SDLengine};
 use doomie::game::Game;
-use sdl2::image::LoadTexture;
+
+const USAGE: &str = "usage: doomie [--config <file>] [--bindings <file>] [--map <file>] [--record <file> | --play <file>]
+              [--width <pixels>] [--height <pixels>] [--fps <n>] [--fov <degrees>]
+              [--num_rays <n>] [--max_depth <n>] [--player_speed <n>]
+              [--mouse_sensitivity <n>] [--stick_dead_zone <n>] [--stick_move_sensitivity <n>]
+              [--stick_turn_sensitivity <n>] [--trigger_threshold <n>] [--floor_color <r,g,b>]
+              [--fog_color <r,g,b>] [--fog_falloff <n>]";
+
+// --record <file> saves the session input, --play <file> replays it
+enum Mode {
+    Live,
+    Record(PathBuf),
+    Play(PathBuf),
+}
+
+struct Options {
+    mode: Mode,
+    bindings: PathBuf,
+    map: PathBuf,
+    config: Config,
+}
+
+fn parse_args() -> Result<Options, Box<dyn Error>> {
+    let mut mode = Mode::Live;
+    let mut bindings = PathBuf::from("bindings.cfg");
+    let mut map = PathBuf::from("assets/maps/level1.map");
+    let mut config = PathBuf::from("doomie.cfg");
+
+    // settings given on the command line win over the ones in the config file
+    let mut overrides = Vec::new();
+
+    let mut args = env::args().skip(1);
+
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or(USAGE)?;
+
+        match flag.as_str() {
+            "--record"   => mode = Mode::Record(value.into()),
+            "--play"     => mode = Mode::Play(value.into()),
+            "--bindings" => bindings = value.into(),
+            "--map"      => map = value.into(),
+            "--config"   => config = value.into(),
+            _ => match flag.strip_prefix("--") {
+                Some(key) if Config::KEYS.contains(&key) => overrides.push((flag.clone(), value)),
+                _ => return Err(USAGE.into()),
+            },
+        }
+    }
+
+    let mut config = Config::load(&config)?;
+
+    for (flag, value) in overrides {
+        config.set(&flag[2..], &value)
+            .and_then(|_| config.validate())
+            .map_err(|e| format!("{}: {}", flag, e))?;
+    }
+
+    Ok(Options { mode, bindings, map, config })
+}
 
 pub fn main() -> Result<(), Box<dyn Error>> {
+    let Options { mode, bindings, map, config } = parse_args()?;
+
+    let keymap = KeyMap::new(&Bindings::load(&bindings)?)?;
+    let map = Map::load(&map)?;
+
     let sdl = sdl2::init()?;
 
     let (clock, events, video) = (sdl.timer()?, sdl.event_pump()?, sdl.video()?);
     let window = video
-        .window("doomie", WIDTH, HEIGHT)
+        .window("doomie", config.width, config.height)
         .position_centered()
+        .resizable()
         .build()?;
     let screen = window.into_canvas().build()?;
     let texture_creator = screen.texture_creator();
 
-    let mut textures = HashMap::new();
-
-    TEXTURES_PATHS.iter().for_each(|(texture_id, path)| {
-        let texture = texture_creator.load_texture(Path::new(path)).unwrap();
+    let mut engine = SDLengine::new(sdl.mouse(), screen, &texture_creator, events, clock, config, keymap);
 
-        textures.insert(*texture_id, ScaledTexture::new(texture));
-    });
+    engine.enable_controllers(sdl.game_controller()?);
 
-    let engine = SDLengine::new(sdl.mouse(), screen, textures, events, clock, FPS);
+    let mut game = Game::new(engine, config, map)?;
 
-    let mut game = Game::new(engine);
+    match &mode {
+        Mode::Live => {}
+        Mode::Record(_) => game.start_recording(),
+        Mode::Play(path) => game.play(Demo::load(path)?)?,
+    }
 
     game.run();
 
+    if let Mode::Record(path) = &mode {
+        game.stop_recording().unwrap().save(path)?;
+    }
+
+    if let Some(divergence) = game.divergence() {
+        eprintln!("{}", divergence);
+    }
+
     Ok(())
 }
\ No newline at end of file
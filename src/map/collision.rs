@@ -0,0 +1,85 @@
+use super::{map::Map, pos::Pos};
+
+// gap kept between a circle and the wall it stops against, so it isn't touching on the next move
+const SKIN: f32 = 1e-4;
+
+// Where a circle of the given radius ends up after trying to move by `delta`. Each axis
+// is moved and resolved on its own, so running into a wall only stops the part of the
+// movement going into it and the rest slides along. Circles already overlapping a wall
+// can still move as long as they don't go further into it
+pub fn slide(map: &Map, pos: Pos, delta: Pos, radius: f32) -> Pos {
+    let x = resolve(map, pos, pos.x() + delta.x(), radius, Axis::X);
+    let pos = Pos::new(x, pos.y());
+    let y = resolve(map, pos, pos.y() + delta.y(), radius, Axis::Y);
+
+    Pos::new(x, y)
+}
+
+// true when the circle overlaps a wall tile
+pub fn collides(map: &Map, pos: Pos, radius: f32) -> bool {
+    walls_touching(map, pos, radius).next().is_some()
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+}
+
+impl Axis {
+    fn get(self, pos: Pos) -> f32 {
+        match self {
+            Axis::X => pos.x(),
+            Axis::Y => pos.y(),
+        }
+    }
+
+    fn set(self, pos: Pos, value: f32) -> Pos {
+        match self {
+            Axis::X => Pos::new(value, pos.y()),
+            Axis::Y => Pos::new(pos.x(), value),
+        }
+    }
+}
+
+// value along the axis the circle can reach on its way to `target`, stopping at the
+// first wall ahead it would overlap
+fn resolve(map: &Map, pos: Pos, target: f32, radius: f32, axis: Axis) -> f32 {
+    let from = axis.get(pos);
+    let forward = target > from;
+
+    // left or top side of each wall in the way, walls behind can only be moved away from
+    let ahead = walls_touching(map, axis.set(pos, target), radius)
+        .map(|(x, y)| axis.get(Pos::new(x as f32, y as f32)))
+        .filter(|near| (near + 0.5 - from) * (target - from) > 0.0);
+
+    let stop = if forward {
+        ahead.map(|near| near - radius - SKIN).reduce(f32::min)
+    } else {
+        ahead.map(|near| near + 1.0 + radius + SKIN).reduce(f32::max)
+    };
+
+    // never backwards, which could push it into another wall
+    match stop {
+        Some(stop) if forward => stop.clamp(from, target),
+        Some(stop)            => stop.clamp(target, from),
+        None                  => target,
+    }
+}
+
+// wall tiles overlapped by the circle
+fn walls_touching(map: &Map, pos: Pos, radius: f32) -> impl Iterator<Item = (i32, i32)> + '_ {
+    let (left, right) = ((pos.x() - radius).floor() as i32, (pos.x() + radius).floor() as i32);
+    let (top, bottom) = ((pos.y() - radius).floor() as i32, (pos.y() + radius).floor() as i32);
+
+    (top..=bottom)
+        .flat_map(move |y| (left..=right).map(move |x| (x, y)))
+        .filter(|&(x, y)| map.is_in_map(x, y) && map.is_wall(Pos::new(x as f32, y as f32)))
+        .filter(move |&(x, y)| {
+            // closest point of the tile to the centre of the circle
+            let closest_x = pos.x().clamp(x as f32, x as f32 + 1.0);
+            let closest_y = pos.y().clamp(y as f32, y as f32 + 1.0);
+
+            (pos.x() - closest_x).hypot(pos.y() - closest_y) < radius
+        })
+}
@@ -0,0 +1,122 @@
+use std::collections::BinaryHeap;
+
+use super::{map::Map, path::{get_graph, Graph, Open}, pos::MapPos};
+
+// Cost of the cheapest route from every floor tile to a goal, usually the player's tile.
+// It's computed once for the whole map and shared by all the enemies, so they know at
+// once whether the goal can be reached, and walking downhill from any tile only looks
+// at its neighbours. Routes follow the same rules as PathFinding
+#[derive(Clone, Debug)]
+pub struct FlowField {
+    width: usize,
+    graph: Graph,
+    distance: Vec<f32>, // infinite when unreachable
+    taken: Vec<bool>, // tiles with an enemy, routes go around them
+    goal: Option<MapPos>,
+}
+
+impl FlowField {
+    pub fn new(map: &Map) -> Self {
+        let graph = get_graph(map);
+
+        FlowField {
+            width: map.width(),
+            distance: vec![f32::INFINITY; graph.len()],
+            taken: vec![false; graph.len()],
+            graph,
+            goal: None,
+        }
+    }
+
+    pub fn goal(&self) -> Option<MapPos> {
+        self.goal
+    }
+
+    // recomputes the distances only when the goal moved to another tile, returns
+    // true when it did
+    pub fn update(&mut self, goal: MapPos) -> bool {
+        if self.goal == Some(goal) {
+            return false;
+        }
+
+        self.goal = Some(goal);
+        self.distance.fill(f32::INFINITY);
+
+        let Some(start) = self.index(goal) else {
+            return true;
+        };
+
+        // steps cost the same both ways except for the tile they end on, so going
+        // backwards from the goal each step pays for the tile it leaves
+        let mut open = BinaryHeap::from([Open { cost: 0.0, index: start }]);
+        self.distance[start] = 0.0;
+
+        while let Some(Open { cost, index: current }) = open.pop() {
+            if cost > self.distance[current] {
+                continue;
+            }
+
+            for &(next, step) in &self.graph[current] {
+                let back = self.step_cost(next, current).unwrap_or(step);
+                let next_cost = cost + back;
+
+                if next_cost < self.distance[next] {
+                    self.distance[next] = next_cost;
+                    open.push(Open { cost: next_cost, index: next });
+                }
+            }
+        }
+
+        true
+    }
+
+    // marks the tiles with an enemy, replacing the previous ones
+    pub fn occupy(&mut self, tiles: impl IntoIterator<Item = MapPos>) {
+        self.taken.fill(false);
+
+        for tile in tiles {
+            if let Some(index) = self.index(tile) {
+                self.taken[index] = true;
+            }
+        }
+    }
+
+    pub fn taken(&self, tile: MapPos) -> bool {
+        self.index(tile).is_some_and(|index| self.taken[index])
+    }
+
+    // cost of the cheapest route from the tile to the goal, None when it can't be reached
+    pub fn distance(&self, tile: MapPos) -> Option<f32> {
+        self.index(tile)
+            .map(|index| self.distance[index])
+            .filter(|distance| distance.is_finite())
+    }
+
+    // neighbouring tile on the cheapest route that isn't taken, the goal itself once there.
+    // None when it can't be reached or every way forward is taken
+    pub fn next_step(&self, from: MapPos) -> Option<MapPos> {
+        let current = self.distance(from)?;
+
+        if current == 0.0 {
+            return Some(from);
+        }
+
+        let index = self.index(from)?;
+
+        self.graph[index]
+            .iter()
+            .filter(|&&(next, _)| !self.taken[next] && self.distance[next] < current)
+            .min_by(|&&(a, step_a), &&(b, step_b)| (step_a + self.distance[a]).total_cmp(&(step_b + self.distance[b])))
+            .map(|&(next, _)| MapPos::new(next % self.width, next / self.width))
+    }
+
+    fn step_cost(&self, from: usize, to: usize) -> Option<f32> {
+        self.graph[from].iter().find(|&&(next, _)| next == to).map(|&(_, cost)| cost)
+    }
+
+    fn index(&self, tile: MapPos) -> Option<usize> {
+        let index = tile.y() * self.width + tile.x();
+
+        (tile.x() < self.width && index < self.distance.len()).then_some(index)
+    }
+}
@@ -1,8 +1,10 @@
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
 use MapObject::*;
 
-use crate::{engine::TextureID};
+use crate::{engine::{wall_texture, TextureID}, observer::fnv1a};
 
-use super::pos::Pos;
+use super::{pos::Pos, spawn::Spawn};
 
 #[derive(Hash, Copy, Clone, Eq, PartialEq, Default, Debug)]
 pub enum MapObject {
@@ -17,50 +19,411 @@ pub enum MapObject {
     M, // Mold,
 }
 
-pub const WIDTH: usize = 16;
-pub const HEIGHT: usize = 9;
-
-pub static MAP: &'static [[MapObject; WIDTH]; HEIGHT] = &[
-    [W, W, W, W, W, W, W, W, M, M, M, W, W, W, W, W],
-    [W, F, F, F, F, F, F, F, F, F, F, F, F, F, F, W],
-    [W, F, F, S, B, B, B, F, F, F, B, B, B, F, F, W],
-    [W, F, F, F, F, F, B, F, F, F, F, F, B, F, F, W],
-    [W, F, F, F, F, F, B, F, F, F, F, F, B, F, F, W],
-    [W, F, F, S, B, B, B, F, F, F, F, F, F, F, F, W],
-    [W, F, F, F, F, F, F, F, F, F, F, F, F, F, F, W],
-    [W, F, F, M, F, F, F, M, F, F, F, F, F, F, F, W],
-    [W, W, W, W, W, W, W, W, G, W, G, W, G, W, W, W],
-];
-
-pub fn is_wall(pos: Pos) -> bool {
-    let map_pos = pos.map();
-
-    is_in_map(map_pos.x(), map_pos.y()) && MAP[map_pos.y()][map_pos.x()] != MapObject::F
+// side of a wall hit by a ray, walls can look different on each so corners read clearly
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Face {
+    NorthSouth,
+    EastWest,
 }
 
-pub fn get_texture(pos: Pos) -> Option<MapObject> {
-    let map_pos = pos.map();
+// textures of the (north/south, east/west) faces
+type Faces = (TextureID, TextureID);
+
+// what is seen above and below a floor tile
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Surface {
+    #[default]
+    Flat, // plain color
+    Texture(TextureID),
+    Sky, // only on the ceiling
+}
 
-    if is_in_map(map_pos.x(), map_pos.y()) {
-        Some(MAP[map_pos.y()][map_pos.x()])
-    } else {
-        None
+impl Surface {
+    // `flat`, `sky` or the number of a wall texture
+    fn from_name(name: &str) -> Option<Surface> {
+        match name {
+            "flat" => Some(Surface::Flat),
+            "sky"  => Some(Surface::Sky),
+            _      => name.parse().ok().and_then(wall_texture).map(Surface::Texture),
+        }
     }
 }
 
-pub fn is_in_map<T>(x: T, y: T) -> bool
-where
-    T: TryInto<usize>,
-{
-    if let (Ok(x_usize), Ok(y_usize)) = (x.try_into(), y.try_into()) {
-        x_usize < WIDTH && y_usize < HEIGHT
-    } else {
-        false
+// (floor, ceiling)
+type Surfaces = (Surface, Surface);
+
+// everything the legend says about a tile, the last ones are its light level and walking cost
+type Entry = (MapObject, Faces, Surfaces, f32, f32);
+
+impl MapObject {
+    // name of the tile in the legend of map files
+    pub fn from_name(name: &str) -> Option<MapObject> {
+        match name {
+            "floor"    => Some(F),
+            "wall"     => Some(W),
+            "gargoyle" => Some(G),
+            "bricks"   => Some(B),
+            "shield"   => Some(S),
+            "mold"     => Some(M),
+            _          => None,
+        }
+    }
+
+    // texture of the wall when the map doesn't pick one
+    fn default_texture(&self) -> TextureID {
+        match self {
+            F | W => TextureID::Wall1,
+            B     => TextureID::Wall2,
+            M     => TextureID::Wall3,
+            G     => TextureID::Wall4,
+            S     => TextureID::Wall5,
+        }
     }
 }
 
-pub fn map_to_texture(map_object: MapObject) -> TextureID {
-    match map_object {
-        _ => TextureID::Wall,
+// Text format, the grid is drawn one character per tile and the legend says what each character is:
+//
+//   [grid]
+//   WWWWW
+//   W...W
+//   WWWWW
+//
+//   [legend]
+//   W = wall
+//   . = floor
+//   B = bricks 2 3      optional wall textures, the second one for the east/west faces
+//   , = floor 3 sky     optional floor and ceiling, a wall texture, `flat` or `sky` for the ceiling
+//   : = floor light 0.5 light level of the sector from 0 (dark) to 1, the default
+//   ~ = floor 4 cost 3  how much enemies avoid walking over it, from 1, the default
+//
+//   [entities]
+//   player 1.5 1.5 0
+//   enemy soldier 3.5 1.5
+//
+// every row must be as wide as the first one, the outer border can't have floor
+// tiles and # starts a comment anywhere but inside the grid. There must be one
+// player start and everything has to be placed on a floor tile
+#[derive(Clone, Debug, PartialEq)]
+pub struct Map {
+    width: usize,
+    height: usize,
+    tiles: Vec<MapObject>,
+    faces: Vec<Faces>,
+    surfaces: Vec<Surfaces>,
+    lights: Vec<f32>,
+    costs: Vec<f32>,
+    spawns: Vec<Spawn>,
+    checksum: u32, // of the file it was read from, demos are tied to it
+}
+
+impl Map {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::parse(&fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut section = "";
+        let mut rows: Vec<(usize, &str)> = Vec::new();
+        let mut legend = HashMap::new();
+        let mut spawns = Vec::new();
+
+        for (num, line) in text.lines().enumerate() {
+            let num = num + 1;
+
+            if section == "grid" && !line.trim().is_empty() && !line.trim().starts_with('[') {
+                rows.push((num, line.trim_end()));
+                continue;
+            }
+
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                section = match name {
+                    "grid" | "legend" | "entities" => name,
+                    _ => return Err(format!("line {}: unknown section [{}]", num, name).into()),
+                };
+                continue;
+            }
+
+            match section {
+                "legend" => {
+                    let (tile, kind) = line.split_once('=')
+                        .ok_or_else(|| format!("line {}: expected `tile = kind`", num))?;
+
+                    let mut chars = tile.trim().chars();
+
+                    let tile = match (chars.next(), chars.next()) {
+                        (Some(tile), None) => tile,
+                        _ => return Err(format!("line {}: tiles are a single character, got {:?}", num, tile.trim()).into()),
+                    };
+
+                    let entry = Self::parse_kind(kind)
+                        .map_err(|e| format!("line {}: {}", num, e))?;
+
+                    legend.insert(tile, entry);
+                }
+                "entities" => {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+
+                    let spawn = Spawn::parse(&fields)
+                        .map_err(|e| format!("line {}: {}", num, e))?;
+
+                    spawns.push((num, spawn));
+                }
+                _ => return Err(format!("line {}: expected a [grid], [legend] or [entities] section", num).into()),
+            }
+        }
+
+        let mut map = Self::from_rows(&rows, &legend)?;
+
+        map.place(&spawns)?;
+        map.checksum = fnv1a(text.bytes());
+
+        Ok(map)
+    }
+
+    fn place(&mut self, spawns: &[(usize, Spawn)]) -> Result<(), Box<dyn Error>> {
+        for (num, spawn) in spawns {
+            let pos = spawn.pos();
+
+            if pos.x() < 0.0 || pos.y() < 0.0 || self.tile(pos.map().x(), pos.map().y()) != Some(F) {
+                return Err(format!("line {}: ({}, {}) is not a floor tile", num, pos.x(), pos.y()).into());
+            }
+
+            if matches!(spawn, Spawn::Player(..)) && self.player_start().is_some() {
+                return Err(format!("line {}: the player can only start once", num).into());
+            }
+
+            self.spawns.push(spawn.clone());
+        }
+
+        if self.player_start().is_none() {
+            return Err("the map has no player start".into());
+        }
+
+        Ok(())
+    }
+
+    // `kind [texture [east/west texture]]` for walls,
+    // `floor [floor [ceiling]] [light level] [cost cost]` for the floor
+    fn parse_kind(text: &str) -> Result<Entry, String> {
+        let fields: Vec<&str> = text.split_whitespace().collect();
+
+        let name = fields.first().ok_or("expected a tile kind")?;
+
+        let object = MapObject::from_name(name)
+            .ok_or_else(|| format!("unknown kind {:?}", name))?;
+
+        if object == F {
+            let settings = fields.iter()
+                .position(|field| *field == "light" || *field == "cost")
+                .unwrap_or(fields.len());
+
+            let surfaces = Self::parse_surfaces(&fields[1..settings])?;
+
+            let (mut light, mut cost) = (1.0, 1.0);
+            let mut settings = fields[settings..].iter();
+
+            while let Some(setting) = settings.next() {
+                let value = settings.next();
+
+                match *setting {
+                    "light" => light = Self::parse_light(value)?,
+                    "cost"  => cost = Self::parse_cost(value)?,
+                    _       => return Err(format!("unknown floor setting {:?}", setting)),
+                }
+            }
+
+            return Ok((object, (object.default_texture(), object.default_texture()), surfaces, light, cost));
+        }
+
+        let textures = fields[1..].iter().map(|field| {
+            field.parse().ok().and_then(wall_texture)
+                .ok_or_else(|| format!("unknown wall texture {:?}", field))
+        }).collect::<Result<Vec<TextureID>, String>>()?;
+
+        let faces = match textures.as_slice() {
+            []                       => (object.default_texture(), object.default_texture()),
+            [texture]                => (*texture, *texture),
+            [north_south, east_west] => (*north_south, *east_west),
+            _                        => return Err("walls have at most two textures".to_string()),
+        };
+
+        Ok((object, faces, Surfaces::default(), 1.0, 1.0))
+    }
+
+    fn parse_light(level: Option<&&str>) -> Result<f32, String> {
+        let level = level.ok_or("expected a light level after `light`")?;
+
+        let light: f32 = level.parse().map_err(|_| format!("invalid light level {:?}", level))?;
+
+        if !(0.0..=1.0).contains(&light) {
+            return Err(format!("light levels go from 0 to 1, got {}", light));
+        }
+
+        Ok(light)
+    }
+
+    fn parse_cost(value: Option<&&str>) -> Result<f32, String> {
+        let value = value.ok_or("expected a walking cost after `cost`")?;
+
+        let cost: f32 = value.parse().map_err(|_| format!("invalid walking cost {:?}", value))?;
+
+        if !(cost >= 1.0 && cost.is_finite()) {
+            return Err(format!("walking costs start at 1, got {}", cost));
+        }
+
+        Ok(cost)
+    }
+
+    fn parse_surfaces(fields: &[&str]) -> Result<Surfaces, String> {
+        let surfaces = fields.iter().map(|field| {
+            Surface::from_name(field).ok_or_else(|| format!("unknown floor texture {:?}", field))
+        }).collect::<Result<Vec<Surface>, String>>()?;
+
+        match surfaces.as_slice() {
+            [Surface::Sky, ..]  => Err("the sky can only be on the ceiling".to_string()),
+            []                  => Ok(Surfaces::default()),
+            [floor]             => Ok((*floor, Surface::Flat)),
+            [floor, ceiling]    => Ok((*floor, *ceiling)),
+            _                   => Err("floors have at most a floor and a ceiling texture".to_string()),
+        }
     }
-}
\ No newline at end of file
+
+    fn from_rows(rows: &[(usize, &str)], legend: &HashMap<char, Entry>) -> Result<Self, Box<dyn Error>> {
+        let width = rows.first().ok_or("the map has no [grid] rows")?.1.chars().count();
+        let height = rows.len();
+
+        let mut tiles = Vec::with_capacity(width * height);
+        let mut faces = Vec::with_capacity(width * height);
+        let mut surfaces = Vec::with_capacity(width * height);
+        let mut lights = Vec::with_capacity(width * height);
+        let mut costs = Vec::with_capacity(width * height);
+
+        for (y, (num, row)) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(format!("line {}: expected {} tiles, found {}", num, width, row.chars().count()).into());
+            }
+
+            for (x, tile) in row.chars().enumerate() {
+                let (object, textures, surface, light, cost) = *legend.get(&tile)
+                    .ok_or_else(|| format!("line {}, column {}: unknown tile {:?}", num, x + 1, tile))?;
+
+                let border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+
+                if border && object == F {
+                    return Err(format!("line {}, column {}: the outer border must be closed", num, x + 1).into());
+                }
+
+                tiles.push(object);
+                faces.push(textures);
+                surfaces.push(surface);
+                lights.push(light);
+                costs.push(cost);
+            }
+        }
+
+        Ok(Map { width, height, tiles, faces, surfaces, lights, costs, spawns: Vec::new(), checksum: 0 })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn spawns(&self) -> &[Spawn] {
+        &self.spawns
+    }
+
+    pub fn checksum(&self) -> u32 {
+        self.checksum
+    }
+
+    // position and facing
+    pub fn player_start(&self) -> Option<(Pos, f32)> {
+        self.spawns.iter().find_map(|spawn| match spawn {
+            Spawn::Player(pos, angle) => Some((*pos, *angle)),
+            _ => None,
+        })
+    }
+
+    pub fn tile(&self, x: usize, y: usize) -> Option<MapObject> {
+        self.is_in_map(x, y).then(|| self.tiles[y * self.width + x])
+    }
+
+    pub fn is_wall(&self, pos: Pos) -> bool {
+        let map_pos = pos.map();
+
+        self.tile(map_pos.x(), map_pos.y()).is_some_and(|object| object != F)
+    }
+
+    pub fn get_texture(&self, pos: Pos, face: Face) -> Option<TextureID> {
+        let map_pos = pos.map();
+
+        if !self.is_wall(pos) {
+            return None;
+        }
+
+        let (north_south, east_west) = self.faces[map_pos.y() * self.width + map_pos.x()];
+
+        match face {
+            Face::NorthSouth => Some(north_south),
+            Face::EastWest   => Some(east_west),
+        }
+    }
+
+    // floor seen at the tile, outside the map it's flat
+    pub fn floor(&self, x: usize, y: usize) -> Surface {
+        self.surfaces(x, y).0
+    }
+
+    pub fn ceiling(&self, x: usize, y: usize) -> Surface {
+        self.surfaces(x, y).1
+    }
+
+    fn surfaces(&self, x: usize, y: usize) -> Surfaces {
+        if self.is_in_map(x, y) { self.surfaces[y * self.width + x] } else { Surfaces::default() }
+    }
+
+    // light level of the tile, outside the map it's fully lit
+    pub fn light(&self, pos: Pos) -> f32 {
+        let map_pos = pos.map();
+
+        if pos.x() >= 0.0 && pos.y() >= 0.0 && self.is_in_map(map_pos.x(), map_pos.y()) {
+            self.lights[map_pos.y() * self.width + map_pos.x()]
+        } else {
+            1.0
+        }
+    }
+
+    // how much walking over the tile costs, 1 for plain floor. Only meaningful on floor tiles
+    pub fn cost(&self, x: usize, y: usize) -> f32 {
+        if self.is_in_map(x, y) { self.costs[y * self.width + x] } else { 1.0 }
+    }
+
+    // false when every floor and ceiling is a plain color under full light
+    pub fn has_surfaces(&self) -> bool {
+        self.surfaces.iter().any(|surfaces| *surfaces != Surfaces::default())
+            || self.lights.iter().any(|light| *light != 1.0)
+    }
+
+    pub fn is_in_map<T>(&self, x: T, y: T) -> bool
+    where
+        T: TryInto<usize>,
+    {
+        if let (Ok(x_usize), Ok(y_usize)) = (x.try_into(), y.try_into()) {
+            x_usize < self.width && y_usize < self.height
+        } else {
+            false
+        }
+    }
+}
+
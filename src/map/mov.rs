@@ -1,6 +1,19 @@
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Mov {
     North,
     West,
     East,
     South,
-}
\ No newline at end of file
+}
+
+impl Mov {
+    // (forward, strafe) relative to where the player looks, strafe grows to the right
+    pub fn direction(&self) -> (f32, f32) {
+        match self {
+            Mov::North => (1.0, 0.0),
+            Mov::South => (-1.0, 0.0),
+            Mov::West  => (0.0, -1.0),
+            Mov::East  => (0.0, 1.0),
+        }
+    }
+}
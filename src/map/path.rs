@@ -1,86 +1,175 @@
-use std::collections::{VecDeque, HashMap};
+use std::{cmp::Ordering, collections::BinaryHeap, f32::consts::SQRT_2};
 
-use super::{map::{is_in_map, is_wall, MapObject, MAP}, pos::{MapPos, Pos}};
+use super::{map::{Map, MapObject}, pos::{MapPos, Pos}, raycasting::line_of_sight};
 
 pub static WAYS: [(i32, i32); 8] = [
     (-1, 0), (0, -1), (1, 0), (0, 1),
     (-1, -1), (1, -1), (1, 1), (-1, 1)
 ];
 
-#[derive(Clone)]
+// distance between the points where `shortcut` checks the floor, in tiles
+const SHORTCUT_STEP: f32 = 0.25;
+
+// (tile index, cost of the step) of the tiles reachable from each one, by tile index
+pub(crate) type Graph = Vec<Vec<(usize, f32)>>;
+
+// Cheapest routes between floor tiles with A*. Steps cost the walking cost of the tile
+// they end on, √2 times that for diagonals, and diagonals can't cut the corner of a wall.
+// Enemies keep the route to the player's tile and walk it straight wherever `shortcut` lets them
+#[derive(Clone, Debug)]
 pub struct PathFinding {
-    // TODO: ejercicio: inyectar el grafo en el constructor
-    graph: HashMap<MapPos, Vec<MapPos>>,
+    width: usize,
+    graph: Graph,
 }
 
 impl PathFinding {
-    pub fn new() -> Self {
-        Self { 
-            graph: get_graph(),
+    pub fn new(map: &Map) -> Self {
+        Self {
+            width: map.width(),
+            graph: get_graph(map),
         }
     }
 
-    pub fn get_path(&self, start: MapPos, goal: MapPos, npc_positions: Vec<MapPos>) -> Option<MapPos> {
-        let visited = self.bfs(start, goal, npc_positions);
-        let mut path = vec![goal];
-        let mut step = visited.get(&goal).copied().unwrap_or(start);
+    // every tile from start to goal, both included. None when it can't be reached
+    pub fn find_path(&self, start: MapPos, goal: MapPos) -> Option<Vec<MapPos>> {
+        let (start, goal) = (self.index(start)?, self.index(goal)?);
 
-        while step != start {
-            path.push(step);
-            step = visited.get(&step).copied().unwrap_or(start);
-        }
+        let mut cost = vec![f32::INFINITY; self.graph.len()];
+        let mut parent = vec![usize::MAX; self.graph.len()];
+        let mut open = BinaryHeap::from([Open { cost: self.heuristic(start, goal), index: start }]);
 
-        path.pop() // Return the last point in the path
-    }
+        cost[start] = 0.0;
+
+        while let Some(Open { index: current, .. }) = open.pop() {
+            if current == goal {
+                let mut path = vec![goal];
 
-    fn bfs(&self, start: MapPos, goal: MapPos, npc_positions: Vec<MapPos>) -> HashMap<MapPos, MapPos> {
-        let mut queue = VecDeque::new();
-        let mut visited = HashMap::new();
+                while *path.last().unwrap() != start {
+                    path.push(parent[*path.last().unwrap()]);
+                }
 
-        queue.push_back(start);
-        visited.insert(start, start);
+                path.reverse();
 
-        while let Some(cur_node) = queue.pop_front() {
-            if cur_node == goal {
-                break;
+                return Some(path.into_iter().map(|index| self.tile(index)).collect());
             }
 
-            if let Some(next_nodes) = self.graph.get(&cur_node) {
-                for &next_node in next_nodes {
-                    if !visited.contains_key(&next_node)
-                        && !npc_positions.contains(&next_node) 
-                    {
-                        queue.push_back(next_node);
-                        visited.insert(next_node, cur_node);
-                    }
+            for &(next, step) in &self.graph[current] {
+                let next_cost = cost[current] + step;
+
+                if next_cost < cost[next] {
+                    cost[next] = next_cost;
+                    parent[next] = current;
+                    open.push(Open { cost: next_cost + self.heuristic(next, goal), index: next });
                 }
             }
         }
 
-        visited
+        None
+    }
+
+    // octile distance, exact on plain floor without walls in the way
+    fn heuristic(&self, from: usize, to: usize) -> f32 {
+        let (from, to) = (self.tile(from), self.tile(to));
+        let dx = from.x().abs_diff(to.x()) as f32;
+        let dy = from.y().abs_diff(to.y()) as f32;
+
+        dx.max(dy) + (SQRT_2 - 1.0) * dx.min(dy)
+    }
+
+    fn index(&self, tile: MapPos) -> Option<usize> {
+        let index = tile.y() * self.width + tile.x();
+
+        (tile.x() < self.width && index < self.graph.len()).then_some(index)
+    }
+
+    fn tile(&self, index: usize) -> MapPos {
+        MapPos::new(index % self.width, index / self.width)
+    }
+}
+
+// A circle of the given radius can walk straight from one point to the other without
+// touching a wall or crossing floor costlier than the tiles at either end, so a route
+// can skip the tiles in between
+pub fn shortcut(map: &Map, from: Pos, to: Pos, radius: f32) -> bool {
+    let d = to - from;
+    let length = d.x().hypot(d.y());
+
+    if length == 0.0 {
+        return true;
     }
+
+    // both sides of the circle, not just its centre
+    let side = Pos::new(-d.y() / length * radius, d.x() / length * radius);
+
+    let clear = [Pos::new(0.0, 0.0), side, Pos::new(-side.x(), -side.y())].into_iter()
+        .all(|offset| line_of_sight(map, from + offset, to + offset));
+
+    let cost = |pos: Pos| map.cost(pos.map().x(), pos.map().y());
+    let max_cost = cost(from).max(cost(to));
+    let samples = (length / SHORTCUT_STEP).ceil() as usize;
+
+    clear && (0..=samples).all(|i| cost(from.lerp(to, i as f32 / samples as f32)) <= max_cost)
+}
+
+// tile waiting in the open set, the cheapest estimate comes out of the heap first
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Open {
+    pub cost: f32,
+    pub index: usize,
 }
 
-fn get_next_nodes(x: i32, y: i32) -> Vec<(i32, i32)> {
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost).then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Open {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Open {}
+
+fn is_floor(map: &Map, x: i32, y: i32) -> bool {
+    map.is_in_map(x, y) && map.tile(x as usize, y as usize) == Some(MapObject::F)
+}
+
+// floor tiles around the given one that can be walked to, with the cost of the step
+fn get_next_nodes(map: &Map, x: i32, y: i32) -> Vec<(i32, i32, f32)> {
     WAYS.iter()
-        .map(|(dx, dy)| (x + *dx as i32, y + *dy as i32))
-        .filter(|&(nx, ny)| is_in_map(nx, ny) && !is_wall(Pos::new(nx as f32, ny as f32))) // Assuming world_map contains coordinates that are blocked
+        .filter(|&&(dx, dy)| is_floor(map, x + dx, y + dy))
+        // both sides of a diagonal must be open, or it would go through the corner
+        .filter(|&&(dx, dy)| dx == 0 || dy == 0 || (is_floor(map, x + dx, y) && is_floor(map, x, y + dy)))
+        .map(|&(dx, dy)| {
+            let (nx, ny) = (x + dx, y + dy);
+            let length = if dx != 0 && dy != 0 { SQRT_2 } else { 1.0 };
+
+            (nx, ny, length * map.cost(nx as usize, ny as usize))
+        })
         .collect()
 }
 
-fn get_graph() -> HashMap<MapPos, Vec<MapPos>> {
-    let mut graph = HashMap::new();
+pub(crate) fn get_graph(map: &Map) -> Graph {
+    let mut graph = vec![Vec::new(); map.width() * map.height()];
 
-    for (y, row) in MAP.iter().enumerate() {
-        for (x, &col) in row.iter().enumerate() {
-            if col == MapObject::F {
-                let next_nodes = get_next_nodes(x as i32, y as i32);
-                graph.entry(MapPos::new(x, y))
-                    .or_insert(Vec::new())
-                    .extend(next_nodes.into_iter().map(|(x, y)| MapPos::new(x as usize, y as usize)));
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            if map.tile(x, y) == Some(MapObject::F) {
+                graph[y * map.width() + x] = get_next_nodes(map, x as i32, y as i32)
+                    .into_iter()
+                    .map(|(nx, ny, cost)| (ny as usize * map.width() + nx as usize, cost))
+                    .collect();
             }
         }
     }
 
     graph
-}
\ No newline at end of file
+}
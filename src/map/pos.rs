@@ -19,6 +19,14 @@ impl Pos {
         self.y
     }
 
+    // linear interpolation, t = 0 gives self and t = 1 gives other
+    pub fn lerp(&self, other: Pos, t: f32) -> Pos {
+        Pos {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+        }
+    }
+
     pub fn map(&self) -> MapPos {
         MapPos {
             x: self.x as usize,
@@ -35,9 +43,9 @@ impl PartialEq for Pos {
 
 impl Eq for Pos {}
 
-impl Into<(u32, u32)> for Pos {
-    fn into(self) -> (u32, u32) {
-        (self.x as u32, self.y as u32)
+impl From<Pos> for (u32, u32) {
+    fn from(pos: Pos) -> Self {
+        (pos.x as u32, pos.y as u32)
     }
 }
 
@@ -72,7 +80,7 @@ impl Sub for Pos {
     }
 }
 
-#[derive(Clone, Copy, Debug, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct MapPos {
     x: usize,
     y: usize,
@@ -92,16 +100,8 @@ impl MapPos {
     }
 }
 
-impl Into<(i32, i32)> for MapPos {
-    fn into(self) -> (i32, i32) {
-        (self.x as i32, self.y as i32)
-    }
-}
-
-impl PartialEq for MapPos {
-    fn eq(&self, other: &Self) -> bool {
-        self.x == other.x && self.y == other.y
+impl From<MapPos> for (i32, i32) {
+    fn from(pos: MapPos) -> Self {
+        (pos.x as i32, pos.y as i32)
     }
 }
-
-impl Eq for MapPos {}
\ No newline at end of file
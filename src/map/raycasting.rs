@@ -1,98 +1,83 @@
-use crate::conf::MAX_DEPTH;
-
 use super::{
-    map::{get_texture, is_wall, MapObject},
+    map::{Face, Map, MapObject},
     pos::{MapPos, Pos},
-    traverse::traverse_grid,
 };
 
-// Function to calculate horizontal intersections
-pub fn calculate_horizontal_intersection(
-    pos: &Pos,
-    map_pos: &MapPos,
-    sin_a: f32,
-    cos_a: f32,
-) -> (f32, f32, Option<MapObject>) {
-    let (y_hor, dy) = if sin_a.is_sign_positive() {
-        ((map_pos.y() + 1) as f32, 1.0)
-    } else {
-        (map_pos.y() as f32 - 1e-6, -1.0)
-    };
-
-    let depth_hor = (y_hor - pos.y()) / sin_a;
-    let x_hor = pos.x() + depth_hor * cos_a;
-    let delta_depth = dy / sin_a;
-    let dx = delta_depth * cos_a;
-
-    // Traverse the grid to find the wall
-    let (depth_hor, x_hor, texture_hor) =
-        traverse_grid(Pos::new(x_hor, y_hor), Pos::new(dx, dy), depth_hor, delta_depth);
-
-    (depth_hor, x_hor, texture_hor)
+// Where a ray first runs into a wall
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RayHit {
+    pub distance: f32, // from the origin, along the ray
+    pub tile: MapObject,
+    pub cell: MapPos,
+    pub side: Face,
+    pub offset: f32, // across the face from 0 to 1, where the texture column is taken
 }
-// Function to calculate vertical intersections
-pub fn calculate_vertical_intersection(
-    pos: &Pos,
-    map_pos: &MapPos,
-    sin_a: f32,
-    cos_a: f32,
-) -> (f32, f32, Option<MapObject>) {
-    let (mut x_vert, dx) = if cos_a.is_sign_positive() {
-        ((map_pos.x() + 1) as f32, 1.0)
-    } else {
-        (map_pos.x() as f32 - 1e-6, -1.0)
-    };
-
-    let mut depth_vert = (x_vert - pos.x()) / cos_a;
-    let mut y_vert = pos.y() + depth_vert * sin_a;
-    let delta_depth = dx as f32 / cos_a;
-    let dy = delta_depth * sin_a;
-    let mut texture_vert = None;
-
-    for _ in 1..MAX_DEPTH {
-        if is_wall(Pos::new(x_vert, y_vert)) {
-            texture_vert = get_texture(Pos::new(x_vert, y_vert));
-            break;
-        }
-        x_vert += dx;
-        y_vert += dy;
-        depth_vert += delta_depth;
-    }
 
-    (depth_vert, y_vert, texture_vert)
+// Walks the grid one tile boundary at a time (DDA) from `origin` until it enters a wall,
+// None when there's none within `max_depth` tiles
+pub fn cast_ray(map: &Map, origin: Pos, angle: f32, max_depth: u32) -> Option<RayHit> {
+    cast(map, origin, angle, max_depth as f32)
 }
 
-// Function to select the closest intersection
-pub fn select_closest_intersection(
-    depth_hor: f32,
-    x_hor: f32,
-    texture_hor: Option<MapObject>,
-    depth_vert: f32,
-    y_vert: f32,
-    texture_vert: Option<MapObject>,
-    sin_a: f32,
-    cos_a: f32,
-) -> (f32, f32, Option<MapObject>) {
-    if depth_vert < depth_hor {
-        let offset = {
-            let y_mod = y_vert % 1.0;
-            if cos_a.is_sign_positive() {
-                y_mod
-            } else {
-                1.0 - y_mod
-            }
+// no wall between the two points
+pub fn line_of_sight(map: &Map, a: Pos, b: Pos) -> bool {
+    let d = b - a;
+
+    cast(map, a, d.y().atan2(d.x()), d.x().hypot(d.y())).is_none()
+}
+
+fn cast(map: &Map, origin: Pos, angle: f32, max_distance: f32) -> Option<RayHit> {
+    let (sin_a, cos_a) = angle.sin_cos();
+
+    let (mut x, mut y) = (origin.x().floor() as i32, origin.y().floor() as i32);
+    let (step_x, step_y) = (if cos_a > 0.0 { 1 } else { -1 }, if sin_a > 0.0 { 1 } else { -1 });
+
+    // distance along the ray between two vertical or two horizontal grid lines
+    let delta_x = if cos_a != 0.0 { (1.0 / cos_a).abs() } else { f32::INFINITY };
+    let delta_y = if sin_a != 0.0 { (1.0 / sin_a).abs() } else { f32::INFINITY };
+
+    // and to the first of each
+    let mut next_x = if cos_a > 0.0 { (x as f32 + 1.0 - origin.x()) * delta_x } else { (origin.x() - x as f32) * delta_x };
+    let mut next_y = if sin_a > 0.0 { (y as f32 + 1.0 - origin.y()) * delta_y } else { (origin.y() - y as f32) * delta_y };
+
+    loop {
+        let (distance, side) = if next_x < next_y {
+            x += step_x;
+            next_x += delta_x;
+
+            (next_x - delta_x, Face::EastWest)
+        } else {
+            y += step_y;
+            next_y += delta_y;
+
+            (next_y - delta_y, Face::NorthSouth)
         };
-        (depth_vert, offset, texture_vert)
-    } else {
-        let offset = {
-            let x_mod = x_hor % 1.0;
-            if sin_a.is_sign_positive() {
-                1.0 - x_mod
-            } else {
-                x_mod
+
+        if distance > max_distance || !map.is_in_map(x, y) {
+            return None;
+        }
+
+        let tile = map.tile(x as usize, y as usize)?;
+
+        if tile == MapObject::F {
+            continue;
+        }
+
+        // textures read left to right as seen from the front of each face
+        let offset = match side {
+            Face::EastWest => {
+                let along = (origin.y() + distance * sin_a).rem_euclid(1.0);
+
+                if cos_a > 0.0 { along } else { 1.0 - along }
+            }
+            Face::NorthSouth => {
+                let along = (origin.x() + distance * cos_a).rem_euclid(1.0);
+
+                if sin_a > 0.0 { 1.0 - along } else { along }
             }
         };
-        (depth_hor, offset, texture_hor)
+
+        return Some(RayHit { distance, tile, cell: MapPos::new(x as usize, y as usize), side, offset });
     }
 }
 
@@ -0,0 +1,81 @@
+use super::pos::Pos;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecorationKind {
+    Candlebra,
+    GreenLight,
+    RedLight,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PickupKind {
+    Health,
+}
+
+impl DecorationKind {
+    pub fn from_name(name: &str) -> Option<DecorationKind> {
+        match name {
+            "candlebra"   => Some(DecorationKind::Candlebra),
+            "green_light" => Some(DecorationKind::GreenLight),
+            "red_light"   => Some(DecorationKind::RedLight),
+            _             => None,
+        }
+    }
+}
+
+impl PickupKind {
+    pub fn from_name(name: &str) -> Option<PickupKind> {
+        match name {
+            "health" => Some(PickupKind::Health),
+            _        => None,
+        }
+    }
+}
+
+// Something placed on the level when it starts, listed in the [entities] section of map files
+#[derive(Clone, PartialEq, Debug)]
+pub enum Spawn {
+    Player(Pos, f32), // facing in radians
+    Enemy(String, Pos), // kinds are looked up in assets/enemies.cfg when the game starts
+    Decoration(DecorationKind, Pos),
+    Pickup(PickupKind, Pos),
+}
+
+impl Spawn {
+    pub fn pos(&self) -> Pos {
+        match self {
+            Spawn::Player(pos, _)
+            | Spawn::Enemy(_, pos)
+            | Spawn::Decoration(_, pos)
+            | Spawn::Pickup(_, pos) => *pos,
+        }
+    }
+
+    // `player <x> <y> <facing in degrees>` or `<enemy|decoration|pickup> <kind> <x> <y>`
+    pub fn parse(fields: &[&str]) -> Result<Spawn, String> {
+        let number = |field: &str| field.parse::<f32>()
+            .map_err(|_| format!("invalid number {:?}", field));
+
+        match fields {
+            ["player", x, y, facing] => {
+                Ok(Spawn::Player(Pos::new(number(x)?, number(y)?), number(facing)?.to_radians()))
+            }
+            ["enemy", kind, x, y] => {
+                Ok(Spawn::Enemy(kind.to_string(), Pos::new(number(x)?, number(y)?)))
+            }
+            ["decoration", kind, x, y] => {
+                let kind = DecorationKind::from_name(kind)
+                    .ok_or_else(|| format!("unknown decoration {:?}", kind))?;
+
+                Ok(Spawn::Decoration(kind, Pos::new(number(x)?, number(y)?)))
+            }
+            ["pickup", kind, x, y] => {
+                let kind = PickupKind::from_name(kind)
+                    .ok_or_else(|| format!("unknown pickup {:?}", kind))?;
+
+                Ok(Spawn::Pickup(kind, Pos::new(number(x)?, number(y)?)))
+            }
+            _ => Err("expected `player <x> <y> <facing>` or `<enemy|decoration|pickup> <kind> <x> <y>`".to_string()),
+        }
+    }
+}
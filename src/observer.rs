@@ -0,0 +1,69 @@
+// Read only snapshot of the world, lets callers inspect the game without rendering it
+
+use crate::ai::AiState;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnemyStatus {
+    Idle,
+    Walking,
+    Shooting,
+    Pain,
+    Dead,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerState {
+    pub life: u32,
+    pub pos: (f32, f32),
+    pub angle: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EnemyState {
+    pub life: i32,
+    pub pos: (f32, f32),
+    pub status: EnemyStatus,
+    pub ai: AiState,
+}
+
+#[derive(Clone, Debug)]
+pub struct WorldState {
+    pub player: PlayerState,
+    pub enemies: Vec<EnemyState>,
+    pub projectiles: Vec<(f32, f32)>, // positions of the ones in flight
+    pub is_over: bool,
+}
+
+impl WorldState {
+    // over the bits of every simulated value
+    pub fn checksum(&self) -> u32 {
+        let mut values = vec![
+            self.player.life,
+            self.player.pos.0.to_bits(),
+            self.player.pos.1.to_bits(),
+            self.player.angle.to_bits(),
+            self.is_over as u32,
+        ];
+
+        for enemy in &self.enemies {
+            values.extend([
+                enemy.life as u32,
+                enemy.pos.0.to_bits(),
+                enemy.pos.1.to_bits(),
+                enemy.status as u32,
+                enemy.ai as u32,
+            ]);
+        }
+
+        for pos in &self.projectiles {
+            values.extend([pos.0.to_bits(), pos.1.to_bits()]);
+        }
+
+        fnv1a(values.iter().flat_map(|value| value.to_le_bytes()))
+    }
+}
+
+// FNV-1a, cheap and stable across runs
+pub fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u32 {
+    bytes.into_iter().fold(0x811c9dc5, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193))
+}
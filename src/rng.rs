@@ -0,0 +1,21 @@
+// Small xorshift generator. The game owns a single one with a fixed seed so every
+// roll of the dice is the same when a demo is replayed
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Rng { state: seed.max(1) }
+    }
+
+    // uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+
+        (self.state >> 8) as f32 / (1 << 24) as f32
+    }
+}
@@ -0,0 +1,82 @@
+use std::rc::Rc;
+
+use crate::{engine::{Driver, Sprite, TextureID}, input::InputState, map::map::Map, texture::TextureRef};
+
+// Wraps another driver replacing its input with a pre-recorded list of
+// held controls and mouse motion, one entry per tick. Pressed and released
+// edges are derived between entries. Once the script runs out everything is released
+pub struct ScriptedDriver<'a> {
+    driver: Box<dyn Driver + 'a>,
+    script: Vec<InputState>,
+    input: InputState,
+    tick: usize,
+}
+
+impl<'a> ScriptedDriver<'a> {
+    pub fn new(driver: Box<dyn Driver + 'a>, script: Vec<InputState>) -> Box<Self> {
+        Box::new(Self { driver, script, input: InputState::new(), tick: 0 })
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+}
+
+impl<'a> Driver for ScriptedDriver<'a> {
+    fn delta_time(&mut self) -> f32 {
+        self.driver.delta_time()
+    }
+
+    fn poll(&mut self) -> InputState {
+        let next = self.script.get(self.tick).cloned().unwrap_or_default();
+
+        self.input.follow(&next);
+        self.tick += 1;
+
+        self.input.clone()
+    }
+
+    fn play_shoot(&mut self) {
+        self.driver.play_shoot();
+    }
+
+    fn play_sound(&mut self, name: &str) {
+        self.driver.play_sound(name);
+    }
+
+    fn play_player_pain(&mut self) {
+        self.driver.play_player_pain();
+    }
+
+    fn render(&mut self, commands: Vec<Sprite>) {
+        self.driver.render(commands);
+    }
+
+    fn get_texture_width(&self, texture_id: TextureID) -> u32 {
+        self.driver.get_texture_width(texture_id)
+    }
+
+    fn get_image_ratio(&self, texture_id: TextureID) -> f32 {
+        self.driver.get_image_ratio(texture_id)
+    }
+
+    fn center_mouse(&self, x: i32, y: i32) {
+        self.driver.center_mouse(x, y);
+    }
+
+    fn load_refs(&self, ids: &[TextureID]) -> Vec<TextureRef> {
+        self.driver.load_refs(ids)
+    }
+
+    fn screen_size(&self) -> (u32, u32) {
+        self.driver.screen_size()
+    }
+
+    fn toggle_fullscreen(&mut self) {
+        self.driver.toggle_fullscreen();
+    }
+
+    fn set_map(&mut self, map: Rc<Map>) {
+        self.driver.set_map(map);
+    }
+}
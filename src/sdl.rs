@@ -1,8 +1,8 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, error::Error, path::Path, rc::Rc};
 
-use sdl2::{event::Event, keyboard::Keycode, mixer::{self, Channel, Chunk, InitFlag, AUDIO_S16LSB, DEFAULT_CHANNELS}, mouse::MouseUtil, pixels::Color, rect::Rect, render::{Texture, WindowCanvas}, EventPump, TimerSubsystem};
+use sdl2::{image::{LoadSurface, LoadTexture}, controller::{Axis as ControllerAxis, GameController}, event::{Event, WindowEvent}, keyboard::Scancode, mixer::{self, Channel, Chunk, InitFlag, AUDIO_S16LSB, DEFAULT_CHANNELS}, mouse::{MouseButton as SDLMouseButton, MouseUtil}, pixels::{Color, PixelFormatEnum}, rect::Rect, render::{BlendMode, Texture, TextureCreator, WindowCanvas}, surface::Surface, video::{FullscreenType, WindowContext}, EventPump, GameControllerSubsystem, TimerSubsystem};
 
-use crate::{conf::{HALF_HEIGHT, HALF_WIDTH, HEIGHT, SCALE, TEXTURE_SIZE, WIDTH}, engine::{digit_to_texture, Controlls, Driver, Sprite, TextureID}, texture::TextureRef};
+use crate::{assets::all_textures, bindings::{Bindings, Button, MouseButton}, conf::{Config, TEXTURE_SIZE}, engine::{digit_to_texture, span_source, Controlls, DepthBuffer, Driver, Sprite, TextureID}, floor::{self, floor_rows, SURFACE_TEXTURES}, headless::{load_some_textures, Image}, input::{Axis, InputState}, map::map::Map, shading::Shade, texture::TextureRef};
 
 pub struct ScaledTexture<'a> {
     pub texture: Texture<'a>,
@@ -10,11 +10,53 @@ pub struct ScaledTexture<'a> {
 }
 
 impl <'a>ScaledTexture<'a> {
-    pub fn new(texture: Texture<'a>) -> ScaledTexture {
+    // walls are sampled as if they were TEXTURE_SIZE wide, whatever their real size
+    pub fn new(texture: Texture<'a>) -> ScaledTexture<'a> {
+        let scale_factor = texture.query().width as f32 / TEXTURE_SIZE as f32;
+
         ScaledTexture {
             texture,
-            scale_factor: 4.0,
+            scale_factor,
+        }
+    }
+}
+
+// bindings resolved to SDL scancodes and mouse buttons
+pub struct KeyMap {
+    keys: HashMap<Scancode, Controlls>,
+    buttons: HashMap<SDLMouseButton, Controlls>,
+}
+
+impl KeyMap {
+    pub fn new(bindings: &Bindings) -> Result<Self, Box<dyn Error>> {
+        let mut keys = HashMap::new();
+        let mut buttons = HashMap::new();
+
+        for (button, control) in bindings.buttons()? {
+            match button {
+                Button::Key(name) => {
+                    let scancode = Scancode::from_name(&name)
+                        .ok_or_else(|| format!("unknown key {:?} bound to {}", name, control.name()))?;
+
+                    keys.insert(scancode, control);
+                }
+                Button::Mouse(button) => {
+                    buttons.insert(to_sdl_button(button), control);
+                }
+            }
         }
+
+        Ok(KeyMap { keys, buttons })
+    }
+}
+
+fn to_sdl_button(button: MouseButton) -> SDLMouseButton {
+    match button {
+        MouseButton::Left   => SDLMouseButton::Left,
+        MouseButton::Middle => SDLMouseButton::Middle,
+        MouseButton::Right  => SDLMouseButton::Right,
+        MouseButton::X1     => SDLMouseButton::X1,
+        MouseButton::X2     => SDLMouseButton::X2,
     }
 }
 
@@ -25,23 +67,39 @@ pub struct SDLengine<'a> {
     clock: TimerSubsystem,
 
     shoot: Chunk,
-    enemy_pain_shout: Chunk,
-    enemy_shoot: Chunk,
     player_pain: Chunk,
+    sounds: HashMap<String, Chunk>, // enemy sounds, loaded the first time they play
     
     textures: HashMap<TextureID, ScaledTexture<'a>>,
-    fps: u32,
+    config: Config,
+    last_frame: u32,
     mouse: MouseUtil,
+    keymap: KeyMap,
+    input: InputState,
+
+    // plugged in game controllers by instance id, None until enabled
+    controllers: Option<(GameControllerSubsystem, HashMap<u32, GameController>)>,
+    active_controller: Option<u32>, // the last one moved, its sticks drive the input
+    trigger_down: bool,
+
+    // floor and ceiling are cast in software, so their textures are also kept in memory
+    map: Option<Rc<Map>>,
+    surface_textures: HashMap<TextureID, Image>,
+    background: Image,
+    creator: &'a TextureCreator<WindowContext>,
+    background_texture: Option<Texture<'a>>, // same size as background, made again on resize
+    silhouettes: HashMap<TextureID, Texture<'a>>, // of the sprites seen through the fog
 }
 
 impl <'a> SDLengine <'a> {
     pub fn new(    
         mouse: MouseUtil,
         screen: WindowCanvas,
-        textures: HashMap<TextureID, ScaledTexture<'a>>,
+        creator: &'a TextureCreator<WindowContext>,
         events: EventPump, 
         clock: TimerSubsystem, 
-        fps: u32
+        config: Config,
+        keymap: KeyMap,
     ) -> Box<Self> {
         mouse.capture(false);
 
@@ -51,66 +109,241 @@ impl <'a> SDLengine <'a> {
         mixer::allocate_channels(32); // Allocate more channels to prevent running out
 
         let shoot = Chunk::from_file("assets/sound/shotgun.wav").unwrap();
-        let enemy_pain_shout = Chunk::from_file("assets/sound/npc_pain.wav").unwrap();
-        let enemy_shoot = Chunk::from_file("assets/sound/npc_attack.wav").unwrap();
         let player_pain = Chunk::from_file("assets/sound/player_pain.wav").unwrap();
 
+        let textures = all_textures().map(|(texture_id, path)| {
+            (texture_id, ScaledTexture::new(creator.load_texture(Path::new(path)).unwrap()))
+        }).collect();
+
+        let surface_textures = load_some_textures(Path::new("."), &SURFACE_TEXTURES).unwrap();
+        let background = Image::new(config.width, config.height);
+
+        let last_frame = clock.ticks();
+
         Box::new(Self {
-            events, clock, screen, fps, textures, mouse,
-            shoot, enemy_pain_shout, enemy_shoot, player_pain,
+            events, clock, screen, config, last_frame, textures, mouse, keymap,
+            input: InputState::new(),
+            shoot, player_pain,
+            sounds: HashMap::new(),
+            controllers: None,
+            active_controller: None,
+            trigger_down: false,
+            map: None,
+            surface_textures,
+            background,
+            creator,
+            background_texture: None,
+            silhouettes: HashMap::new(),
         })
     }
 
-    fn render_texture(&mut self, texture_id: TextureID, pos: (f32, f32), proj_width: f32, proj_height: f32) {
-        let texture  = self.textures.get(&texture_id).unwrap();
+    // controllers connected before or after this call are picked up through
+    // the device added events, so they can be plugged while the game runs
+    pub fn enable_controllers(&mut self, subsystem: GameControllerSubsystem) {
+        self.controllers = Some((subsystem, HashMap::new()));
+    }
+
+    fn controller_added(&mut self, index: u32) {
+        if let Some((subsystem, controllers)) = self.controllers.as_mut() {
+            match subsystem.open(index) {
+                Ok(controller) => {
+                    controllers.insert(controller.instance_id(), controller);
+                }
+                Err(e) => eprintln!("Failed to open game controller {}: {}", index, e),
+            }
+        }
+    }
 
-        let location = Rect::new(pos.0 as i32, pos.1 as i32, proj_width as u32, proj_height as u32);
+    fn controller_removed(&mut self, instance_id: u32) {
+        let remaining = self.controllers.as_mut().map_or(0, |(_, controllers)| {
+            controllers.remove(&instance_id);
+            controllers.len()
+        });
+
+        // the one still in use keeps what it's holding
+        if remaining > 0 && self.active_controller != Some(instance_id) {
+            return;
+        }
 
-        self.screen.copy(&texture.texture, None, location).unwrap();
+        // let go of whatever the controller was holding
+        self.active_controller = None;
+        self.input.set_axis(Axis::Forward, 0.0);
+        self.input.set_axis(Axis::Strafe, 0.0);
+        self.input.set_axis(Axis::Turn, 0.0);
+        self.controller_trigger(0.0);
     }
 
-    fn render_background(&mut self) {
-        let black_half = Rect::new(0, 0,                  WIDTH as u32, HALF_HEIGHT as u32);
-        let gray_half  = Rect::new(0, HALF_HEIGHT as i32, WIDTH as u32, HALF_HEIGHT as u32);
+    fn controller_axis(&mut self, instance_id: u32, axis: ControllerAxis, value: i16) {
+        let value = value as f32 / i16::MAX as f32;
 
-        // Fill the rectangles with black and gray colors
+        self.active_controller = Some(instance_id);
+
+        match axis {
+            ControllerAxis::LeftY        => self.input.set_axis(Axis::Forward, -value),
+            ControllerAxis::LeftX        => self.input.set_axis(Axis::Strafe, value),
+            ControllerAxis::RightX       => self.input.set_axis(Axis::Turn, value),
+            ControllerAxis::TriggerRight => self.controller_trigger(value),
+            _ => {}
+        }
+    }
+
+    fn controller_trigger(&mut self, value: f32) {
+        let down = value > self.config.trigger_threshold;
+
+        if down && !self.trigger_down {
+            self.input.press(Controlls::Enter);
+        } else if !down && self.trigger_down {
+            self.input.release(Controlls::Enter);
+        }
+
+        self.trigger_down = down;
+    }
+
+    // drawn in vertical slices, leaving out the columns where a wall is closer
+    #[allow(clippy::too_many_arguments)]
+    fn render_texture(&mut self, depth_buffer: &DepthBuffer, depth: f32, texture_id: TextureID, pos: (f32, f32), proj_width: f32, proj_height: f32, shade: Shade) {
+        let texture  = self.textures.get_mut(&texture_id).unwrap();
+        let query    = texture.texture.query();
+
+        texture.texture.set_color_mod(shade.light, shade.light, shade.light);
+
+        // the fog is drawn over the sprite with a white copy of it, so it only covers its shape
+        let fog = (shade.fog > 0).then(|| {
+            let silhouette = self.silhouettes.entry(texture_id).or_insert_with(|| silhouette(self.creator, texture_id));
+            let (r, g, b) = self.config.fog_color;
+
+            silhouette.set_color_mod(r, g, b);
+            silhouette.set_alpha_mod(shade.fog);
+            &*silhouette
+        });
+
+        let (x, width) = (pos.0 as i32, proj_width as u32);
+
+        for span in depth_buffer.visible_spans(x, width, depth) {
+            let (source_x, source_width) = span_source(span, x, width, query.width);
+
+            let portion  = Rect::new(source_x, 0, source_width, query.height);
+            let location = Rect::new(span.0, pos.1 as i32, (span.1 - span.0) as u32, proj_height as u32);
+
+            self.screen.copy(&texture.texture, portion, location).unwrap();
+
+            if let Some(silhouette) = fog {
+                self.screen.copy(silhouette, portion, location).unwrap();
+            }
+        }
+    }
+
+    fn render_background(&mut self, camera: ((f32, f32), f32)) {
+        if let Some(map) = self.map.clone().filter(|map| map.has_surfaces()) {
+            return self.cast_background(&map, camera);
+        }
+
+        let (width, half_height) = (self.config.width, self.config.half_height());
+
+        let black_half = Rect::new(0, 0, width, half_height);
+
+        // Fill the top half with black and the floor row by row, darker further away
         self.screen.set_draw_color(Color::RGB(0, 0, 0));
         self.screen.fill_rect(black_half).unwrap();
-        self.screen.set_draw_color(Color::RGB(128, 128, 128));
-        self.screen.fill_rect(gray_half).unwrap();
+
+        for (row, [r, g, b, _]) in floor_rows(&self.config).into_iter().enumerate() {
+            self.screen.set_draw_color(Color::RGB(r, g, b));
+            self.screen.fill_rect(Rect::new(0, (half_height as usize + row) as i32, width, 1)).unwrap();
+        }
+    }
+
+    // drawn into a frame sized image that is copied into a streaming texture every frame
+    fn cast_background(&mut self, map: &Map, camera: ((f32, f32), f32)) {
+        let (width, height) = (self.config.width, self.config.height);
+
+        if (self.background.width(), self.background.height()) != (width, height) {
+            self.background = Image::new(width, height);
+            self.background_texture = None;
+        }
+
+        floor::cast(&mut self.background, &self.surface_textures, map, camera, &self.config);
+
+        let texture = self.background_texture.get_or_insert_with(|| {
+            self.creator.create_texture_streaming(PixelFormatEnum::RGBA32, width, height).unwrap()
+        });
+
+        let pixels = self.background.pixels();
+        let row = (width * 4) as usize;
+
+        texture.with_lock(None, |buffer, pitch| {
+            for (y, line) in pixels.chunks_exact(row).enumerate() {
+                buffer[y * pitch..y * pitch + row].copy_from_slice(line);
+            }
+        }).unwrap();
+
+        self.screen.copy(texture, None, None).unwrap();
     }
 
-    fn render_column(&mut self, texture_id: &TextureID, num: usize, offset: f32, proj_height: f32) {
-        let texture  = self.textures.get(&texture_id).unwrap();
+    // texture size once scaled to the window height, for the HUD
+    fn hud_size(&self, texture_id: TextureID) -> (u32, u32) {
+        let query = self.textures.get(&texture_id).unwrap().texture.query();
+        let scale = self.config.hud_scale();
+
+        ((query.width as f32 * scale) as u32, (query.height as f32 * scale) as u32)
+    }
 
-        let origin   = (offset * (TEXTURE_SIZE - SCALE) as f32 * texture.scale_factor) as i32;
+    fn render_column(&mut self, texture_id: TextureID, num: usize, offset: f32, proj_height: f32, shade: Shade) {
+        let texture  = self.textures.get_mut(&texture_id).unwrap();
+        let scale    = self.config.scale();
 
-        let portion  = Rect::new(origin, 0, SCALE, TEXTURE_SIZE * 15);
-        let location = Rect::new((num * (SCALE as usize)) as i32,
-                                        HALF_HEIGHT   as i32 - (proj_height / 2.0) as i32, 
-                                        SCALE         as u32, 
-                                        proj_height   as u32);
+        texture.texture.set_color_mod(shade.light, shade.light, shade.light);
+
+        let origin   = (offset * (TEXTURE_SIZE - scale) as f32 * texture.scale_factor) as i32;
+
+        let portion  = Rect::new(origin, 0, scale, TEXTURE_SIZE * 15);
+        let location = Rect::new((num * (scale as usize)) as i32,
+                                        self.config.half_height() as i32 - (proj_height / 2.0) as i32, 
+                                        scale, 
+                                        proj_height as u32);
 
         self.screen.copy(&texture.texture, portion, location).unwrap();
+
+        // walls are opaque, so the fog goes over the whole column
+        if shade.fog > 0 {
+            let (r, g, b) = self.config.fog_color;
+
+            self.screen.set_blend_mode(BlendMode::Blend);
+            self.screen.set_draw_color(Color::RGBA(r, g, b, shade.fog));
+            self.screen.fill_rect(location).unwrap();
+        }
     }
 }
 
-impl <'a> Driver for SDLengine<'a> {
-    // fn delta_time(&mut self) -> f32 {
-    //     self.clock.delay(1000 / self.fps);
-    //     self.clock.ticks() as f32 / 1000.0
-    // }
+// texture with the shape of the given one in plain white, tinted to draw things over it
+fn silhouette(creator: &TextureCreator<WindowContext>, texture_id: TextureID) -> Texture<'_> {
+    let (_, path) = all_textures().find(|(id, _)| *id == texture_id).unwrap();
+    let mut surface = Surface::from_file(Path::new(path)).unwrap().convert_format(PixelFormatEnum::RGBA32).unwrap();
+
+    surface.with_lock_mut(|pixels| pixels.chunks_exact_mut(4).for_each(|pixel| pixel[..3].fill(255)));
 
+    let mut texture = creator.create_texture_from_surface(&surface).unwrap();
+
+    texture.set_blend_mode(BlendMode::Blend);
+    texture
+}
+
+impl <'a> Driver for SDLengine<'a> {
+    // caps the frame rate at fps, returns the seconds elapsed since the previous call
     fn delta_time(&mut self) -> f32 {
-        let start = self.clock.ticks();
+        let frame = 1000 / self.config.fps;
+        let spent = self.clock.ticks() - self.last_frame;
+
+        if spent < frame {
+            self.clock.delay(frame - spent);
+        }
 
-        self.clock.delay(self.fps);
+        let now = self.clock.ticks();
 
-        let end = self.clock.ticks();
+        let delta = now - self.last_frame;
 
-        let delta = end - start;
+        self.last_frame = now;
 
-        delta as f32
+        delta as f32 / 1000.0
     }
 
     // Reserve channel 0 for shoot sounds
@@ -120,16 +353,17 @@ impl <'a> Driver for SDLengine<'a> {
         }
     }
 
-    fn play_enemy_shoot(&mut self) {
-        if let Err(e) = Channel(1).play(&self.enemy_shoot, 0) {
-            eprintln!("Failed to play shoot sound on channel 0: {}", e);
+    // Enemy sounds take the first free channel
+    fn play_sound(&mut self, name: &str) {
+        if !self.sounds.contains_key(name) {
+            match Chunk::from_file(format!("assets/sound/{}.wav", name)) {
+                Ok(chunk) => { self.sounds.insert(name.to_string(), chunk); }
+                Err(e) => return eprintln!("Failed to load sound {}: {}", name, e),
+            }
         }
-    }
 
-    // Use other channels for pain shouts
-    fn play_pain_shout(&mut self) {
-        if let Err(e) = Channel(2).play(&self.enemy_pain_shout, 0) {
-            eprintln!("Failed to play pain shout sound on channel 1: {}", e);
+        if let Err(e) = Channel::all().play(&self.sounds[name], 0) {
+            eprintln!("Failed to play sound {}: {}", name, e);
         }
     }
 
@@ -140,40 +374,72 @@ impl <'a> Driver for SDLengine<'a> {
         }
     }
 
-    fn poll(&mut self) -> Option<Controlls> {
-        match self.events.poll_iter().next() {
-            Some(event) => {
-                match event {
-                    Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                        Some(Controlls::Escape)
-                    },
-                    Event::KeyDown { keycode: Some(Keycode::W), .. } => {
-                        Some(Controlls::ArrowUp)
-                    },
-                    Event::KeyDown { keycode: Some(Keycode::S), .. } => {
-                        Some(Controlls::ArrowDown)
-                    },
-                    Event::KeyDown { keycode: Some(Keycode::A), .. } => {
-                        Some(Controlls::ArrowLeft)
-                    },
-                    Event::KeyDown { keycode: Some(Keycode::D), .. } => {
-                        Some(Controlls::ArrowRight)
-                    },
-                    Event::MouseMotion { x, xrel, .. } => {
-                        Some(Controlls::Spin(x, xrel))
+    fn poll(&mut self) -> InputState {
+        self.input.clear_edges();
+
+        let events: Vec<Event> = self.events.poll_iter().collect();
+
+        for event in events {
+            match event {
+                Event::Quit { .. } => self.input.press(Controlls::Escape),
+                Event::KeyDown { scancode: Some(scancode), repeat: false, .. } => {
+                    if let Some(control) = self.keymap.keys.get(&scancode) {
+                        self.input.press(*control);
                     }
-                    Event::KeyDown { keycode: Some(Keycode::Space), .. } => {
-                        Some(Controlls::Enter)
-                    },
-                    _ => None
                 }
-            },
-            None => None,
+                Event::KeyUp { scancode: Some(scancode), .. } => {
+                    if let Some(control) = self.keymap.keys.get(&scancode) {
+                        self.input.release(*control);
+                    }
+                }
+                Event::MouseButtonDown { mouse_btn, .. } => {
+                    if let Some(control) = self.keymap.buttons.get(&mouse_btn) {
+                        self.input.press(*control);
+                    }
+                }
+                Event::MouseButtonUp { mouse_btn, .. } => {
+                    if let Some(control) = self.keymap.buttons.get(&mouse_btn) {
+                        self.input.release(*control);
+                    }
+                }
+                Event::MouseMotion { x, xrel, .. } => self.input.move_mouse(x, xrel),
+                Event::ControllerDeviceAdded { which, .. } => self.controller_added(which),
+                Event::ControllerDeviceRemoved { which, .. } => self.controller_removed(which),
+                Event::ControllerAxisMotion { which, axis, value, .. } => self.controller_axis(which, axis, value),
+                Event::Window { win_event: WindowEvent::SizeChanged(width, height), .. } => {
+                    self.config.resize(width as u32, height as u32);
+                }
+                _ => {}
+            }
         }
+
+        self.input.clone()
     }
 
     fn center_mouse(&self, x: i32, y: i32) {
-        self.mouse.warp_mouse_in_window(&self.screen.window(), x, y);
+        self.mouse.warp_mouse_in_window(self.screen.window(), x, y);
+    }
+
+    fn screen_size(&self) -> (u32, u32) {
+        (self.config.width, self.config.height)
+    }
+
+    // desktop fullscreen keeps the screen resolution, the window is just resized to cover it
+    fn toggle_fullscreen(&mut self) {
+        let window = self.screen.window_mut();
+
+        let state = match window.fullscreen_state() {
+            FullscreenType::Off => FullscreenType::Desktop,
+            _                   => FullscreenType::Off,
+        };
+
+        if let Err(e) = window.set_fullscreen(state) {
+            eprintln!("Failed to toggle fullscreen: {}", e);
+        }
+    }
+
+    fn set_map(&mut self, map: Rc<Map>) {
+        self.map = Some(map);
     }
 
     fn get_texture_width(&self, texture_id: TextureID) -> u32 {
@@ -201,23 +467,24 @@ impl <'a> Driver for SDLengine<'a> {
     fn render(&mut self, mut commands: Vec<Sprite>) {
         commands.sort_by(|a, b| b.z_order().partial_cmp(&a.z_order()).unwrap());
 
+        let depth_buffer = DepthBuffer::new(&commands, self.config.width, self.config.scale());
+
         commands.into_iter().for_each(|command| {
             match command {
-                Sprite::Background => self.render_background(),
-                Sprite::Column(_, texture_id, num, offset, proj_height) => {
-                    self.render_column(&texture_id, num, offset, proj_height);
+                Sprite::Background(pos, angle) => self.render_background((pos, angle)),
+                Sprite::Column(_, texture_id, num, offset, proj_height, shade) => {
+                    self.render_column(texture_id, num, offset, proj_height, shade);
                 }
-                Sprite::Sprite(_, texture_id, pos, proj_width, proj_height) => {
-                    self.render_texture(texture_id, pos, proj_width, proj_height);
+                Sprite::Sprite(depth, texture_id, pos, proj_width, proj_height, shade) => {
+                    self.render_texture(&depth_buffer, depth, texture_id, pos, proj_width, proj_height, shade);
                 }
                 Sprite::DrawWeapon(texture_id) => {
+                    let (width, height) = self.hud_size(texture_id);
                     let texture  = self.textures.get(&texture_id).unwrap();
-                    let width    = texture.texture.query().width;
-                    let height   = texture.texture.query().height;
 
                     let location = Rect::new(
-                                    (HALF_WIDTH - (width / 4)) as i32, 
-                                    HEIGHT as i32 - (height / 2) as i32,
+                                    self.config.half_width() as i32 - (width / 4) as i32, 
+                                    self.config.height as i32 - (height / 2) as i32,
                                     width / 2, 
                                     height / 2);
 
@@ -226,7 +493,7 @@ impl <'a> Driver for SDLengine<'a> {
                 Sprite::PainScreen => {
                     let texture = self.textures.get(&TextureID::PainScreen).unwrap();
 
-                    let location = Rect::new(0, 0, WIDTH, HEIGHT);
+                    let location = Rect::new(0, 0, self.config.width, self.config.height);
 
                     self.screen.copy(&texture.texture, None, location).unwrap();
                 }
@@ -236,11 +503,9 @@ impl <'a> Driver for SDLengine<'a> {
                     for (i, digit) in counter.char_indices() {
                         let texture_id = digit_to_texture(digit);
 
+                        let (width, height) = self.hud_size(texture_id);
                         let texture = self.textures.get(&texture_id).unwrap();
 
-                        let width = texture.texture.query().width;
-                        let height = texture.texture.query().height;
-
                         let location = Rect::new((i as u32 * width) as i32, 0, width, height);
 
                         self.screen.copy(&texture.texture, None, location).unwrap();
@@ -249,7 +514,7 @@ impl <'a> Driver for SDLengine<'a> {
                 Sprite::GameOver => {
                     let texture = self.textures.get(&TextureID::GameOver).unwrap();
 
-                    let location = Rect::new(0, 0, WIDTH, HEIGHT);
+                    let location = Rect::new(0, 0, self.config.width, self.config.height);
 
                     self.screen.copy(&texture.texture, None, location).unwrap();
                 }
@@ -1,8 +1,12 @@
-use crate::{conf::FPS, engine::TextureID, texture::TextureRef};
+use crate::{conf::{ENEMY_PAIN_TIME, TICK}, engine::TextureID, texture::TextureRef};
+
+// directions an enemy can be seen from, 45 degrees apart
+pub const ROTATIONS: usize = 8;
 
 #[derive(Clone)]
 pub struct EnemySequence {
-    pub walking_textures: Sequence,
+    pub idle_textures: Directional,
+    pub walking_textures: Directional,
     pub dying_textures: Sequence,
     pub shooting_textures: Sequence,
     pub pain_textures: Sequence,
@@ -10,17 +14,25 @@ pub struct EnemySequence {
 
 impl EnemySequence {
     pub fn new(
-        walking_textures: Vec<TextureRef>,
+        idle_textures: Vec<Vec<TextureRef>>,
+        walking_textures: Vec<Vec<TextureRef>>,
         shooting_textures: Vec<TextureRef>,
         pain_texture: Vec<TextureRef>,
         dying_textures: Vec<TextureRef>,
+        attack_time: f32,
     ) -> Self {
-        let walking_textures = Sequence::new(walking_textures, FPS * 2);
-        let shooting_textures = Sequence::new(shooting_textures, FPS * 3);
-        let pain_textures = Sequence::new(pain_texture, FPS * 3);
-        let dying_textures = Sequence::new(dying_textures, FPS);
+        // the attack frames are spread over the whole attack
+        let shooting_time = attack_time / shooting_textures.len().max(1) as f32;
+        let pain_time = ENEMY_PAIN_TIME / pain_texture.len().max(1) as f32;
+
+        let idle_textures = Directional::new(idle_textures, 2.0);
+        let walking_textures = Directional::new(walking_textures, 2.0);
+        let shooting_textures = Sequence::new(shooting_textures, shooting_time);
+        let pain_textures = Sequence::new(pain_texture, pain_time);
+        let dying_textures = Sequence::new(dying_textures, 1.0);
 
         EnemySequence {
+            idle_textures,
             walking_textures,
             shooting_textures,
             pain_textures,
@@ -29,39 +41,72 @@ impl EnemySequence {
     }
 }
 
+// The same animation drawn from every direction, the first one is the front and the
+// rest follow turning the enemy clockwise on the map. A set with fewer directions
+// than ROTATIONS spreads them evenly, with a single one it looks the same from everywhere
+#[derive(Clone)]
+pub struct Directional {
+    sequences: Vec<Sequence>,
+}
+
+impl Directional {
+    pub fn new(directions: Vec<Vec<TextureRef>>, frame_time: f32) -> Self {
+        Directional {
+            sequences: directions.into_iter().map(|textures| Sequence::new(textures, frame_time)).collect(),
+        }
+    }
+
+    // every direction advances together so turning doesn't restart the animation
+    pub fn next(&mut self) {
+        self.sequences.iter_mut().for_each(Sequence::next);
+    }
+
+    // rotation goes from 0 (facing the camera) to ROTATIONS - 1
+    pub fn get_texture(&self, rotation: usize) -> TextureRef {
+        let direction = rotation % ROTATIONS * self.sequences.len() / ROTATIONS;
+
+        self.sequences[direction].get_texture()
+    }
+}
+
 #[derive(Clone)]
 pub struct Sequence {
     textures: Vec<TextureRef>,
     current_texture: usize,
-    animation_count: u32,
+    elapsed: f32,
     blocked: bool,
 
-    count: u32,
+    // seconds each texture stays on screen
+    frame_time: f32,
 }
 
 impl Sequence {
-    pub fn new(textures: Vec<TextureRef>, count: u32) -> Self {
+    pub fn new(textures: Vec<TextureRef>, frame_time: f32) -> Self {
         Sequence {
             textures,
             current_texture: 0,
-            animation_count: 0,
+            elapsed: 0.0,
             blocked: false,
-            count,
+            frame_time,
         }
     }
 
     fn texture_timeout(&self) -> bool {
-        self.animation_count >= self.count
-    }
-
-    pub fn done(&self) -> bool {
-        self.last_frame() && self.texture_timeout()
+        self.elapsed >= self.frame_time
     }
 
     pub fn get_texture(&self) -> TextureRef {
         self.textures[self.current_texture]
     }
 
+    pub fn frame(&self) -> usize {
+        self.current_texture
+    }
+
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
     pub fn get_texture_id(&self) -> TextureID {
         self.textures[self.current_texture].id()
     }
@@ -72,16 +117,17 @@ impl Sequence {
 
     pub fn reset(&mut self) {
         self.current_texture = 0;
-        self.animation_count = 0;
+        self.elapsed = 0.0;
         self.unblock();
     }
 
+    // advances the animation by one simulation tick
     pub fn next(&mut self) {
         if self.blocked {
             return;
         }
 
-        self.animation_count += 1;
+        self.elapsed += TICK;
 
         if self.texture_timeout() {
             if self.textures.len() == 1 {
@@ -89,7 +135,7 @@ impl Sequence {
             }
 
             self.next_frame();
-            self.animation_count = 0;
+            self.elapsed = 0.0;
         }
     }
 
@@ -0,0 +1,34 @@
+use crate::conf::Config;
+
+// How something is lit. Drivers darken each texel by the light the same way SDL applies
+// a color mod and then blend it towards the fog color:
+// texel * light / 255 * (1 - fog / 255) + fog_color * fog / 255
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Shade {
+    pub light: u8, // color multiplier of every channel, 255 leaves the texel as it is
+    pub fog: u8, // how much of the fog color covers it, 255 hides the texel completely
+}
+
+pub const FULL_LIGHT: Shade = Shade { light: 255, fog: 0 };
+
+// how much of something at `depth` is hidden by the fog, from 0 to 1
+pub fn fog(depth: f32, config: &Config) -> f32 {
+    1.0 - (-depth.max(0.0) / config.fog_falloff).exp()
+}
+
+// `light` of the sector from 0 (dark) to 1, with `fog` from 0 to 1 in front of it
+pub fn shade(light: f32, fog: f32) -> Shade {
+    let byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    Shade { light: byte(light), fog: byte(fog) }
+}
+
+pub fn apply([r, g, b, a]: [u8; 4], shade: Shade, (fr, fg, fb): (u8, u8, u8)) -> [u8; 4] {
+    let (light, fog) = (shade.light as u32, shade.fog as u32);
+
+    let channel = |color: u8, fog_color: u8| {
+        ((color as u32 * light / 255 * (255 - fog) + fog_color as u32 * fog) / 255) as u8
+    };
+
+    [channel(r, fr), channel(g, fg), channel(b, fb), a]
+}
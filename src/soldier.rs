@@ -1,24 +0,0 @@
-use crate::engine::TextureID;
-
-pub static SOLDIER_DYING: [TextureID; 8] = [
-    TextureID::SoldierDead1,
-    TextureID::SoldierDead2,
-    TextureID::SoldierDead3,
-    TextureID::SoldierDead4,
-    TextureID::SoldierDead5,
-    TextureID::SoldierDead6,
-    TextureID::SoldierDead7,
-    TextureID::SoldierDead8,
-];
-
-pub static SOLDIER_PAIN: [TextureID; 1] = [TextureID::SoldierPain];
-
-pub static SOLDIER_WALKING: [TextureID; 4] = [
-    TextureID::SoldierWalking1,
-    TextureID::SoldierWalking2,
-    TextureID::SoldierWalking3,
-    TextureID::SoldierWalking4,
-];
-
-pub static SOLDIER_SHOTING: [TextureID; 2] =
-    [TextureID::SoldierShooting1, TextureID::SoldierShooting2];
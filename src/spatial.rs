@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use crate::map::pos::Pos;
+
+// gap left between actors pushed apart, so they aren't touching on the next move
+const SKIN: f32 = 1e-4;
+
+// something solid moving around the map, a circle seen from above
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Actor {
+    pub id: usize,
+    pub pos: Pos,
+    pub radius: f32,
+}
+
+impl Actor {
+    // true when it overlaps the circle
+    pub fn overlaps(&self, pos: Pos, radius: f32) -> bool {
+        self.distance(pos) < radius + self.radius
+    }
+
+    fn distance(&self, pos: Pos) -> f32 {
+        let d = self.pos - pos;
+
+        d.x().hypot(d.y())
+    }
+}
+
+// Actors bucketed by the tile they stand on, so finding the ones around a point only
+// looks at a few tiles instead of at every actor. It's meant to be cleared and filled
+// again every tick, the buckets are kept so that doesn't allocate
+#[derive(Clone, Debug, Default)]
+pub struct SpatialHash {
+    actors: Vec<Actor>,
+    cells: HashMap<(i32, i32), Vec<usize>>, // indices into actors
+    max_radius: f32,
+}
+
+impl SpatialHash {
+    pub fn clear(&mut self) {
+        self.actors.clear();
+        self.cells.values_mut().for_each(Vec::clear);
+        self.max_radius = 0.0;
+    }
+
+    pub fn insert(&mut self, actor: Actor) {
+        self.cells.entry(cell(actor.pos)).or_default().push(self.actors.len());
+        self.max_radius = self.max_radius.max(actor.radius);
+        self.actors.push(actor);
+    }
+
+    // actors overlapping the circle
+    pub fn overlapping(&self, pos: Pos, radius: f32) -> impl Iterator<Item = &Actor> + '_ {
+        let reach = (radius + self.max_radius).ceil() as i32;
+        let (x, y) = cell(pos);
+
+        (y - reach..=y + reach)
+            .flat_map(move |cy| (x - reach..=x + reach).map(move |cx| (cx, cy)))
+            .filter_map(|key| self.cells.get(&key))
+            .flatten()
+            .map(|&index| &self.actors[index])
+            .filter(move |actor| actor.overlaps(pos, radius))
+    }
+
+    // actors crossed by a line `length` long, nearest first, with how far along the line
+    // it goes into each of them
+    pub fn along(&self, origin: Pos, angle: f32, length: f32) -> Vec<(Actor, f32)> {
+        let (cos_a, sin_a) = (angle.cos(), angle.sin());
+        let mut indices = Vec::new();
+
+        // the cells a tile or less apart along the line, and those around them where
+        // an actor could still reach it from
+        for step in 0..=length.ceil() as usize {
+            let step = (step as f32).min(length);
+            let point = origin + Pos::new(cos_a * step, sin_a * step);
+
+            indices.extend(self.near_cells(point).flatten().copied());
+        }
+
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut hits: Vec<(Actor, f32)> = indices.into_iter()
+            .map(|index| self.actors[index])
+            .filter_map(|actor| {
+                let d = actor.pos - origin;
+                let along = d.x() * cos_a + d.y() * sin_a;
+                let across = (d.x() * sin_a - d.y() * cos_a).abs();
+
+                if across >= actor.radius {
+                    return None;
+                }
+
+                let entry = (along - (actor.radius.powi(2) - across.powi(2)).sqrt()).max(0.0);
+
+                (along + actor.radius >= 0.0 && entry <= length).then_some((actor, entry))
+            })
+            .collect();
+
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        hits
+    }
+
+    fn near_cells(&self, pos: Pos) -> impl Iterator<Item = &Vec<usize>> + '_ {
+        let reach = self.max_radius.ceil() as i32 + 1;
+        let (x, y) = cell(pos);
+
+        (y - reach..=y + reach)
+            .flat_map(move |cy| (x - reach..=x + reach).map(move |cx| (cx, cy)))
+            .filter_map(|key| self.cells.get(&key))
+    }
+}
+
+// Shortest move that takes a circle at `pos` out of the actor, nothing when they don't
+// overlap. When both are at the same spot it goes in a direction picked from the id
+pub fn separation(actor: &Actor, pos: Pos, radius: f32) -> Pos {
+    let overlap = radius + actor.radius - actor.distance(pos) + SKIN;
+
+    if overlap <= SKIN {
+        return Pos::new(0.0, 0.0);
+    }
+
+    let d = pos - actor.pos;
+    let distance = d.x().hypot(d.y());
+
+    let (cos_a, sin_a) = if distance > 0.0 {
+        (d.x() / distance, d.y() / distance)
+    } else {
+        ((actor.id as f32).cos(), (actor.id as f32).sin())
+    };
+
+    Pos::new(cos_a * overlap, sin_a * overlap)
+}
+
+fn cell(pos: Pos) -> (i32, i32) {
+    (pos.x().floor() as i32, pos.y().floor() as i32)
+}
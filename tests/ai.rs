@@ -0,0 +1,102 @@
+use doomie::{
+    ai::{AiState::{self, *}, Brain, Senses},
+    conf::{ENEMY_PAIN_TIME, ENEMY_REACTION_TIME, TICK},
+    enemy_kinds::EnemyDefinition,
+};
+
+const NOTHING: Senses = Senses { sees_player: false, heard_gunfire: false, in_range: false, life: 1.0 };
+
+fn seeing() -> Senses {
+    Senses { sees_player: true, ..NOTHING }
+}
+
+fn in_range() -> Senses {
+    Senses { sees_player: true, in_range: true, ..NOTHING }
+}
+
+fn coward() -> EnemyDefinition {
+    EnemyDefinition { flee_below: 0.5, ..EnemyDefinition::default() }
+}
+
+// state after thinking with the same senses for `seconds`
+fn after(brain: &mut Brain, senses: Senses, kind: &EnemyDefinition, seconds: f32) -> AiState {
+    for _ in 0..(seconds / TICK).ceil() as usize {
+        brain.think(&senses, kind);
+    }
+
+    brain.state()
+}
+
+#[test]
+fn enemies_wait_until_they_notice_the_player() {
+    let kind = EnemyDefinition::default();
+
+    assert_eq!(Idle.next(10.0, &NOTHING, &kind), Idle);
+    assert_eq!(Idle.next(0.0, &seeing(), &kind), Alert);
+    assert_eq!(Idle.next(0.0, &Senses { heard_gunfire: true, ..NOTHING }, &kind), Alert);
+}
+
+#[test]
+fn alerted_enemies_react_after_a_moment() {
+    let kind = EnemyDefinition::default();
+    let mut brain = Brain::default();
+
+    assert_eq!(after(&mut brain, seeing(), &kind, TICK), Alert);
+    assert_eq!(after(&mut brain, NOTHING, &kind, ENEMY_REACTION_TIME / 2.0), Alert);
+    assert_eq!(after(&mut brain, NOTHING, &kind, ENEMY_REACTION_TIME), Chase);
+
+    // once chasing, losing sight of the player doesn't make them forget
+    assert_eq!(after(&mut brain, NOTHING, &kind, 10.0), Chase);
+}
+
+#[test]
+fn attacks_last_their_attack_time() {
+    let kind = EnemyDefinition::default();
+
+    assert_eq!(Chase.next(0.0, &in_range(), &kind), Attack);
+    assert_eq!(Attack.next(kind.attack_time / 2.0, &NOTHING, &kind), Attack);
+    assert_eq!(Attack.next(kind.attack_time, &in_range(), &kind), Chase);
+}
+
+#[test]
+fn hurt_enemies_flee_and_shoot_back_now_and_then() {
+    let kind = coward();
+    let hurt = Senses { life: 0.4, ..in_range() };
+
+    assert_eq!(Chase.next(0.0, &hurt, &kind), Flee);
+    assert_eq!(Attack.next(kind.attack_time, &hurt, &kind), Flee);
+    assert_eq!(Flee.next(0.0, &hurt, &kind), Flee);
+    assert_eq!(Flee.next(10.0, &hurt, &kind), Attack);
+
+    // brave kinds never flee
+    assert_eq!(Chase.next(0.0, &hurt, &EnemyDefinition::default()), Attack);
+}
+
+#[test]
+fn getting_hit_interrupts_with_a_moment_of_pain() {
+    let kind = EnemyDefinition::default();
+    let mut brain = Brain::default();
+
+    assert_eq!(after(&mut brain, in_range(), &kind, 1.0), Attack);
+
+    brain.hurt();
+
+    assert_eq!(brain.state(), Pain);
+    assert_eq!(after(&mut brain, in_range(), &kind, ENEMY_PAIN_TIME / 2.0), Pain);
+    assert_eq!(after(&mut brain, NOTHING, &kind, ENEMY_PAIN_TIME), Chase);
+
+    // cowards come out of it running
+    assert_eq!(Pain.next(ENEMY_PAIN_TIME, &Senses { life: 0.4, ..NOTHING }, &coward()), Flee);
+}
+
+#[test]
+fn dead_enemies_stay_dead() {
+    let kind = EnemyDefinition::default();
+    let dead = Senses { life: 0.0, ..in_range() };
+
+    for state in [Idle, Alert, Chase, Attack, Flee, Pain] {
+        assert_eq!(state.next(0.0, &dead, &kind), Dead);
+    }
+
+    assert_eq!(Dead.next(0.0, &in_range(), &kind), Dead);
+}
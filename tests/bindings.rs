@@ -0,0 +1,39 @@
+use doomie::{
+    bindings::{Bindings, Button, MouseButton},
+    engine::Controlls,
+};
+
+#[test]
+fn defaults_are_used_for_actions_not_in_the_file() {
+    let buttons = Bindings::parse("forward = Z\nstrafe_left = Q\n").unwrap().buttons().unwrap();
+
+    assert_eq!(buttons[&Button::Key("Z".to_string())], Controlls::ArrowUp);
+    assert_eq!(buttons[&Button::Key("Q".to_string())], Controlls::ArrowLeft);
+    assert_eq!(buttons[&Button::Key("D".to_string())], Controlls::ArrowRight);
+    assert_eq!(buttons[&Button::Mouse(MouseButton::Left)], Controlls::Enter);
+    assert_eq!(buttons[&Button::Key("E".to_string())], Controlls::Use);
+    assert!(!buttons.contains_key(&Button::Key("W".to_string())));
+}
+
+#[test]
+fn a_button_bound_twice_is_rejected() {
+    let error = Bindings::parse("fire = A").unwrap_err();
+
+    assert_eq!(error.to_string(), "A is bound to both strafe_left and fire");
+}
+
+#[test]
+fn unknown_actions_report_their_line() {
+    let error = Bindings::parse("# comment\n\njump = Space").unwrap_err();
+
+    assert_eq!(error.to_string(), "line 3: unknown action \"jump\"");
+}
+
+#[test]
+fn use_can_be_rebound() {
+    let buttons = Bindings::parse("use = F, MouseRight").unwrap().buttons().unwrap();
+
+    assert_eq!(buttons[&Button::Key("F".to_string())], Controlls::Use);
+    assert_eq!(buttons[&Button::Mouse(MouseButton::Right)], Controlls::Use);
+    assert!(!buttons.contains_key(&Button::Key("E".to_string())));
+}
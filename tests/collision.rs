@@ -0,0 +1,95 @@
+mod common;
+
+use doomie::{
+    conf::{Config, PLAYER_RADIUS, TICK_RATE},
+    engine::Controlls::{ArrowLeft, ArrowUp},
+    input::InputState,
+    map::{collision::{collides, slide}, map::Map, pos::Pos},
+};
+
+use common::{game, level1, level1_from};
+
+const STEP: f32 = 0.05;
+
+// takes `steps` small steps in the direction, checking it never ends up inside a wall
+fn walk(map: &Map, from: (f32, f32), (dx, dy): (f32, f32), steps: usize) -> Pos {
+    let mut pos = Pos::new(from.0, from.1);
+
+    for _ in 0..steps {
+        pos = slide(map, pos, Pos::new(dx * STEP, dy * STEP), PLAYER_RADIUS);
+
+        assert!(!collides(map, pos, PLAYER_RADIUS), "inside a wall at ({}, {})", pos.x(), pos.y());
+    }
+
+    pos
+}
+
+fn assert_near(pos: Pos, (x, y): (f32, f32)) {
+    assert!((pos.x() - x).abs() < 1e-3 && (pos.y() - y).abs() < 1e-3, "expected ({}, {}), got ({}, {})", x, y, pos.x(), pos.y());
+}
+
+fn level() -> Map {
+    Map::parse(&level1()).unwrap()
+}
+
+#[test]
+fn walking_diagonally_into_the_outer_walls_slides_along_them() {
+    let map = level();
+
+    // north, south, west and east walls
+    assert_near(walk(&map, (7.5, 1.5), (1.0, -1.0), 20), (8.5, 1.0 + PLAYER_RADIUS));
+    assert_near(walk(&map, (10.5, 7.5), (1.0, 1.0), 20), (11.5, 8.0 - PLAYER_RADIUS));
+    assert_near(walk(&map, (1.5, 6.5), (-1.0, -1.0), 20), (1.0 + PLAYER_RADIUS, 5.5));
+    assert_near(walk(&map, (14.5, 6.5), (1.0, -1.0), 20), (15.0 - PLAYER_RADIUS, 5.5));
+}
+
+#[test]
+fn walking_diagonally_into_the_bricks_slides_along_them() {
+    let map = level();
+
+    // the west face of the bricks at (6, 3) and the south face of the ones at (10..12, 2)
+    assert_near(walk(&map, (5.5, 3.5), (1.0, 1.0), 20), (6.0 - PLAYER_RADIUS, 4.5));
+    assert_near(walk(&map, (10.5, 3.5), (1.0, -1.0), 20), (11.5, 3.0 + PLAYER_RADIUS));
+}
+
+#[test]
+fn corners_stop_both_axes() {
+    let map = level();
+
+    assert_near(walk(&map, (14.5, 7.5), (1.0, 1.0), 20), (15.0 - PLAYER_RADIUS, 8.0 - PLAYER_RADIUS));
+    assert_near(walk(&map, (1.5, 1.5), (-1.0, -1.0), 20), (1.0 + PLAYER_RADIUS, 1.0 + PLAYER_RADIUS));
+}
+
+#[test]
+fn the_tip_of_a_wall_is_walked_around() {
+    let map = level();
+
+    // along the east face of the bricks at (12, 2..4) and around their bottom corner
+    let pos = walk(&map, (13.5, 3.5), (-0.5, 1.0), 40);
+
+    assert!((pos.y() - 5.5).abs() < 1e-3 && pos.x() < 13.0 + PLAYER_RADIUS / 2.0, "stuck at ({}, {})", pos.x(), pos.y());
+}
+
+#[test]
+fn moving_away_from_a_wall_is_never_blocked() {
+    let map = level();
+
+    // overlapping the north wall, as if it had been placed there
+    let pos = slide(&map, Pos::new(7.5, 1.1), Pos::new(0.1, 0.1), PLAYER_RADIUS);
+
+    assert_near(pos, (7.6, 1.2));
+}
+
+#[test]
+fn the_player_slides_along_the_wall_it_walks_into() {
+    // at the top of the level looking east, walking forward and to the left goes north east
+    let mut game = game(&level1_from("player 7.5 1.5 0"), vec![InputState::holding(&[ArrowUp, ArrowLeft]); 30]);
+
+    game.run_ticks(30);
+
+    let (x, y) = game.observe().player.pos;
+    let step = Config::default().player_speed / TICK_RATE as f32 / 2f32.sqrt();
+
+    assert!((x - (7.5 + 30.0 * step)).abs() < 1e-3);
+    assert!((y - (1.0 + PLAYER_RADIUS)).abs() < 1e-3);
+}
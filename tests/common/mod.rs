@@ -0,0 +1,122 @@
+// shared by the tests that look at what the game draws
+#![allow(dead_code)]
+
+use std::{cell::RefCell, fs, path::Path, rc::Rc};
+
+use doomie::{
+    conf::Config,
+    engine::{Driver, Sprite, TextureID},
+    game::Game,
+    headless::{load_textures, HeadlessEngine},
+    input::InputState,
+    map::map::Map,
+    script::ScriptedDriver,
+    engine::Controlls::Escape,
+    texture::TextureRef,
+};
+
+// headless driver that keeps a copy of every frame it is asked to draw
+pub struct Recorder {
+    engine: Box<HeadlessEngine>,
+    frames: Rc<RefCell<Vec<Vec<Sprite>>>>,
+}
+
+impl Driver for Recorder {
+    fn delta_time(&mut self) -> f32 { self.engine.delta_time() }
+    fn poll(&mut self) -> InputState { self.engine.poll() }
+    fn play_shoot(&mut self) {}
+    fn play_sound(&mut self, _: &str) {}
+    fn play_player_pain(&mut self) {}
+    fn center_mouse(&self, _: i32, _: i32) {}
+    fn screen_size(&self) -> (u32, u32) { self.engine.screen_size() }
+    fn toggle_fullscreen(&mut self) {}
+
+    fn render(&mut self, commands: Vec<Sprite>) {
+        self.frames.borrow_mut().push(commands.clone());
+        self.engine.render(commands);
+    }
+
+    fn get_texture_width(&self, texture_id: TextureID) -> u32 {
+        self.engine.get_texture_width(texture_id)
+    }
+
+    fn get_image_ratio(&self, texture_id: TextureID) -> f32 {
+        self.engine.get_image_ratio(texture_id)
+    }
+
+    fn load_refs(&self, ids: &[TextureID]) -> Vec<TextureRef> {
+        self.engine.load_refs(ids)
+    }
+
+    fn set_map(&mut self, map: Rc<Map>) {
+        self.engine.set_map(map);
+    }
+}
+
+pub fn root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+pub fn engine(config: Config) -> Box<HeadlessEngine> {
+    HeadlessEngine::new(load_textures(root()).unwrap(), config)
+}
+
+// map file with the grid drawn with `W` walls and `.` floor, plus any other legend lines
+pub fn level(grid: &str, legend: &str, entities: &str) -> String {
+    format!("[grid]\n{}\n[legend]\nW = wall\n. = floor\n{}\n[entities]\n{}", grid, legend, entities)
+}
+
+// a long corridor with the player at its west end looking east, and the other entities
+pub fn corridor(entities: &str) -> String {
+    level("WWWWWWWWWWWW\nW..........W\nWWWWWWWWWWWW", "", &format!("player 1.5 1.5 0\n{}", entities))
+}
+
+// a ring of floor around a single walled-in tile at 3.5 3.5, whatever stands there can
+// never reach the rest of the map or see past the walls, plus the other entities
+pub fn walled_in(entities: &str) -> String {
+    level("WWWWWWW\nW.....W\nW.WWW.W\nW.W.W.W\nW.WWW.W\nW.....W\nWWWWWWW", "", entities)
+}
+
+// the first level as it is in assets/maps
+pub fn level1() -> String {
+    fs::read_to_string(root().join("assets/maps/level1.map")).unwrap()
+}
+
+// the first level with the camera at `player`
+pub fn level1_from(player: &str) -> String {
+    level1().lines()
+        .map(|line| if line.starts_with("player") { player } else { line })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// game of `level` played with the script, drawn headless with the default config
+pub fn game(level: &str, script: Vec<InputState>) -> Box<Game<'static>> {
+    configured_game(level, Config::default(), script)
+}
+
+pub fn configured_game(level: &str, config: Config, script: Vec<InputState>) -> Box<Game<'static>> {
+    Game::new(ScriptedDriver::new(engine(config), script), config, Map::parse(level).unwrap()).unwrap()
+}
+
+// commands of the first frame the game draws of `level`
+pub fn first_frame(level: &str, config: Config) -> Vec<Sprite> {
+    frames(level, config, 1).remove(0)
+}
+
+// commands of every frame drawn over the first `ticks` ticks without input,
+// one per tick when the config runs at the tick rate
+pub fn frames(level: &str, config: Config, ticks: usize) -> Vec<Vec<Sprite>> {
+    let frames = Rc::new(RefCell::new(Vec::new()));
+    let recorder = Box::new(Recorder { engine: engine(config), frames: frames.clone() });
+
+    let mut script = vec![InputState::new(); ticks];
+    script.push(InputState::holding(&[Escape]));
+
+    let mut game = Game::new(ScriptedDriver::new(recorder, script), config, Map::parse(level).unwrap()).unwrap();
+
+    game.run();
+
+    let frames = frames.borrow().clone();
+    frames
+}
@@ -0,0 +1,60 @@
+use doomie::conf::Config;
+
+#[test]
+fn settings_not_in_the_file_keep_their_default() {
+    let config = Config::parse("width = 1280 # smaller window\nfov = 60\nfloor_color = 10, 20, 30\nfog_color = 0, 0, 40\n").unwrap();
+
+    assert_eq!(config.width, 1280);
+    assert!((config.fov - 60f32.to_radians()).abs() < 1e-6);
+    assert_eq!(config.floor_color, (10, 20, 30));
+    assert_eq!(config.fog_color, (0, 0, 40));
+    assert_eq!(config.height, Config::default().height);
+    assert_eq!(config.num_rays, Config::default().num_rays);
+}
+
+#[test]
+fn invalid_values_report_their_line() {
+    let error = Config::parse("\nfps = fast").unwrap_err();
+
+    assert_eq!(error.to_string(), "line 2: invalid value \"fast\" for fps");
+
+    let error = Config::parse("speed = 2").unwrap_err();
+
+    assert_eq!(error.to_string(), "line 1: unknown setting \"speed\"");
+}
+
+#[test]
+fn out_of_range_settings_are_rejected() {
+    let error = Config::parse("width = 400").unwrap_err();
+
+    assert_eq!(error.to_string(), "num_rays must be between 1 and 400, got 800");
+
+    assert!(Config::parse("fov = 180").is_err());
+    assert!(Config::parse("player_speed = 0").is_err());
+    assert!(Config::parse("fog_falloff = 0").is_err());
+    assert!(Config::parse("stick_dead_zone = 1").is_err());
+    assert!(Config::parse("stick_turn_sensitivity = -3").is_err());
+    assert!(Config::parse("trigger_threshold = 1.5").is_err());
+}
+
+#[test]
+fn controller_tuning_is_read_like_the_mouse() {
+    let config = Config::parse("stick_dead_zone = 0.1\nstick_move_sensitivity = 0.8\nstick_turn_sensitivity = 2\ntrigger_threshold = 0.3\n").unwrap();
+
+    assert_eq!(config.stick_dead_zone, 0.1);
+    assert_eq!(config.stick_move_sensitivity, 0.8);
+    assert_eq!(config.stick_turn_sensitivity, 2.0);
+    assert_eq!(config.trigger_threshold, 0.3);
+}
+
+#[test]
+fn resizing_keeps_the_column_width() {
+    let mut config = Config::default();
+
+    config.resize(1280, 720);
+
+    assert_eq!((config.width, config.height), (1280, 720));
+    assert_eq!(config.num_rays, 640);
+    assert_eq!(config.scale(), Config::default().scale());
+    assert!((config.hud_scale() - 0.8).abs() < 1e-6);
+}
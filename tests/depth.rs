@@ -0,0 +1,86 @@
+mod common;
+
+use doomie::{conf::Config, engine::{DepthBuffer, Driver, Sprite}};
+
+use common::{engine, level1_from};
+
+fn first_frame(player: &str) -> Vec<Sprite> {
+    common::first_frame(&level1_from(player), Config::default())
+}
+
+// (depth, x, width) of the soldier sprites
+fn soldiers(commands: &[Sprite]) -> Vec<(f32, i32, u32)> {
+    commands.iter().filter_map(|command| match command {
+        Sprite::Sprite(depth, texture_id, pos, width, ..) if format!("{:?}", texture_id).starts_with("Soldier") => {
+            Some((*depth, pos.0 as i32, *width as u32))
+        }
+        _ => None,
+    }).collect()
+}
+
+fn visible_width(commands: &[Sprite], (depth, x, width): (f32, i32, u32)) -> i32 {
+    let config = Config::default();
+
+    DepthBuffer::new(commands, config.width, config.scale())
+        .visible_spans(x, width, depth)
+        .iter()
+        .map(|(start, end)| end - start)
+        .sum()
+}
+
+#[test]
+fn a_soldier_in_the_open_is_fully_drawn() {
+    // just below the first soldier looking north
+    let commands = first_frame("player 10.4 6.5 270");
+
+    let soldier = soldiers(&commands)[0];
+
+    assert_eq!(visible_width(&commands, soldier), soldier.2 as i32);
+}
+
+#[test]
+fn a_soldier_behind_a_corner_is_clipped() {
+    // the corner of the brick wall at (12, 5) sits right between the camera and the soldier
+    let commands = first_frame("player 13.5 6.5 225");
+
+    let soldier = soldiers(&commands)[0];
+    let visible = visible_width(&commands, soldier);
+
+    assert!(0 < visible && visible < soldier.2 as i32, "{} of {} columns visible", visible, soldier.2);
+}
+
+#[test]
+fn the_headless_driver_only_draws_sprites_in_front_of_walls() {
+    let commands = first_frame("player 13.5 6.5 225");
+
+    let walls: Vec<Sprite> = commands.iter().copied()
+        .filter(|command| !matches!(command, Sprite::Sprite(..)))
+        .collect();
+
+    let mut with_sprites = engine(Config::default());
+    let mut without_sprites = engine(Config::default());
+
+    with_sprites.render(commands.clone());
+    without_sprites.render(walls);
+
+    let (a, b) = (with_sprites.frame(), without_sprites.frame());
+
+    let config = Config::default();
+    let depth_buffer = DepthBuffer::new(&commands, config.width, config.scale());
+
+    let spans: Vec<(i32, i32)> = commands.iter().flat_map(|command| match command {
+        Sprite::Sprite(depth, _, pos, width, ..) => depth_buffer.visible_spans(pos.0 as i32, *width as u32, *depth),
+        _ => vec![],
+    }).collect();
+
+    let changed: Vec<i32> = (0..a.width())
+        .filter(|&x| (0..a.height()).any(|y| a.pixel(x, y) != b.pixel(x, y)))
+        .map(|x| x as i32)
+        .collect();
+
+    assert!(!changed.is_empty());
+
+    for x in changed {
+        assert!(spans.iter().any(|&(start, end)| start <= x && x < end), "column {} was drawn over a closer wall", x);
+    }
+}
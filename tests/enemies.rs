@@ -0,0 +1,95 @@
+mod common;
+
+use doomie::{
+    conf::Config,
+    enemy_kinds::EnemyKinds,
+    engine::Sprite,
+    game::Game,
+    map::map::Map,
+    script::ScriptedDriver,
+};
+
+use common::{corridor, engine, game, level};
+
+// a long open corridor with the player at its west end looking east
+fn facing(enemy: &str) -> String {
+    corridor(&format!("enemy {} 6.5 1.5", enemy))
+}
+
+#[test]
+fn the_bundled_kinds_are_all_defined() {
+    let kinds = EnemyKinds::bundled().unwrap();
+
+    let soldier = kinds.get("soldier").unwrap();
+    let caco = kinds.get("caco_demon").unwrap();
+    let cyber = kinds.get("cyber_demon").unwrap();
+
+    assert_eq!((soldier.life, soldier.damage, soldier.attack_range), (100, 5, 8.0));
+    assert!(caco.attack_range > 1.0 && caco.shift < 0.0, "the caco demon floats and attacks from afar");
+    assert!(cyber.life > soldier.life && cyber.speed < soldier.speed, "the cyber demon is a slow tank");
+}
+
+#[test]
+fn definitions_report_their_mistakes() {
+    let all = "[soldier]\n[caco_demon]\n[cyber_demon]\n";
+    let error = |text: &str| EnemyKinds::parse(text).unwrap_err().to_string();
+
+    assert!(EnemyKinds::parse(all).is_ok());
+    assert_eq!(error("life = 10\n[soldier]"), "line 1: settings must follow a `[kind]` line");
+    assert_eq!(error(&format!("{}[imp]", all)), "line 4: no sprites for \"imp\" in assets/sprites/npc");
+    assert_eq!(error(&format!("{}[soldier]", all)), "line 4: soldier is defined twice");
+    assert_eq!(error(&format!("{}life = x", all)), "line 4: invalid value \"x\" for life");
+    assert_eq!(error(&format!("{}speed = -1", all)), "cyber_demon: speed can't be negative");
+}
+
+#[test]
+fn maps_can_only_place_defined_kinds() {
+    let error = Game::new(ScriptedDriver::new(engine(Config::default()), vec![]), Config::default(), Map::parse(&facing("imp")).unwrap());
+
+    assert_eq!(error.err().unwrap().to_string(), "unknown enemy \"imp\"");
+}
+
+#[test]
+fn ranged_kinds_attack_from_afar() {
+    let mut soldier = game(&facing("soldier"), vec![]);
+    let mut caco = game(&facing("caco_demon"), vec![]);
+
+    // both see the player and react at once
+    soldier.run_ticks(120);
+    caco.run_ticks(120);
+
+    assert!(soldier.observe().projectiles.is_empty());
+    assert_eq!(caco.observe().projectiles.len(), 1, "the caco demon throws a fireball");
+}
+
+#[test]
+fn each_kind_is_drawn_with_its_own_sprites() {
+    for (name, prefix) in [("soldier", "Soldier"), ("caco_demon", "CacoDemon"), ("cyber_demon", "CyberDemon")] {
+        let textures: Vec<String> = common::first_frame(&facing(name), Config::default()).iter().filter_map(|command| match command {
+            Sprite::Sprite(_, texture_id, ..) => Some(format!("{:?}", texture_id)),
+            _ => None,
+        }).collect();
+
+        assert!(textures.iter().any(|texture| texture.starts_with(prefix)), "no {} in {:?}", prefix, textures);
+    }
+}
+
+#[test]
+fn chasing_enemies_cut_straight_across_open_rooms() {
+    let grid = ["WWWWWWWWWWWWWWWW"; 10].iter().enumerate()
+        .map(|(y, row)| if y == 0 || y == 9 { row.to_string() } else { format!("W{}W", ".".repeat(14)) })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // too far to shoot, it has to walk over
+    let mut game = game(&level(&grid, "", "player 1.5 1.5 0\nenemy soldier 13.5 7.5"), vec![]);
+
+    game.run_ticks(600);
+
+    let (x, y) = game.observe().enemies[0].pos;
+    let heading = (y - 7.5).atan2(x - 13.5);
+    let straight = (1.5f32 - 7.5).atan2(1.5 - 13.5);
+
+    // going from tile to tile it would set off diagonally, 18 degrees off
+    assert!((heading - straight).abs().to_degrees() < 3.0, "heading {} instead of {}", heading.to_degrees(), straight.to_degrees());
+}
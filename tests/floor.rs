@@ -0,0 +1,91 @@
+mod common;
+
+use std::{f32::consts::PI, rc::Rc};
+
+use doomie::{
+    conf::Config,
+    engine::{Driver, Sprite, TextureID},
+    floor::SURFACE_TEXTURES,
+    headless::{load_some_textures, HeadlessEngine, Image},
+    map::map::Map,
+};
+
+// the left column of the room has a plain floor and ceiling, the rest a textured floor and no roof
+fn room() -> Map {
+    Map::parse(&common::level("WWWWW\nW.,,W\nW.,,W\nWWWWW", ", = floor 2 sky", "player 1.5 1.5 0")).unwrap()
+}
+
+// colors are compared as they are in the textures
+fn no_fog() -> Config {
+    Config { fog_falloff: f32::MAX, ..Config::default() }
+}
+
+// background alone seen from the camera
+fn frame(camera: ((f32, f32), f32), config: Config) -> Image {
+    let textures = load_some_textures(common::root(), &SURFACE_TEXTURES).unwrap();
+
+    let mut engine = HeadlessEngine::new(textures, config);
+
+    engine.set_map(Rc::new(room()));
+    engine.render(vec![Sprite::Background(camera.0, camera.1)]);
+
+    engine.frame().clone()
+}
+
+fn texture(texture_id: TextureID) -> Image {
+    load_some_textures(common::root(), &SURFACE_TEXTURES).unwrap().remove(&texture_id).unwrap()
+}
+
+#[test]
+fn the_floor_shows_the_texture_of_the_tile_below() {
+    let config = no_fog();
+    let frame = frame(((1.5, 1.5), 0.0), config);
+
+    // the bottom row in the middle of the screen looks straight ahead, at the
+    // depth a wall would need to be to fill the whole screen
+    let row = (config.height - config.half_height() - 1) as f32;
+    let depth = 0.5 * config.screen_dist() / (row + 0.5);
+
+    let floor = texture(TextureID::Wall2);
+    let u = 1.5 + depth - 2.0;
+
+    let expected = floor.pixel((u * floor.width() as f32) as u32, floor.height() / 2);
+
+    assert_eq!(frame.pixel(config.half_width(), config.height - 1), expected);
+}
+
+#[test]
+fn flat_tiles_keep_the_plain_colors() {
+    let config = no_fog();
+    let (r, g, b) = config.floor_color;
+
+    // looking west only the left tile, with the plain floor, is close enough to be seen at the bottom
+    let frame = frame(((1.9, 1.5), PI), config);
+
+    assert_eq!(frame.pixel(config.half_width(), config.height - 1), [r, g, b, 255]);
+    assert_eq!(frame.pixel(config.half_width(), 0), [0, 0, 0, 255]);
+}
+
+#[test]
+fn the_sky_scrolls_with_the_camera() {
+    let config = no_fog();
+    let sky = texture(TextureID::Sky);
+
+    // the sky wraps four times around a full turn, an eighth of a turn is half of the texture
+    for (angle, x) in [(0.0, 0), (PI / 4.0, sky.width() / 2)] {
+        let frame = frame(((2.2, 1.5), angle), config);
+
+        assert_eq!(frame.pixel(config.half_width(), 0), sky.pixel(x, 0));
+    }
+}
+
+#[test]
+fn the_floor_fades_into_the_fog() {
+    let config = Config::default();
+    let frame = frame(((1.9, 1.5), PI), config);
+
+    let near = frame.pixel(config.half_width(), config.height - 1);
+    let far = frame.pixel(config.half_width(), config.half_height() + 10);
+
+    assert!(far[0] < near[0] && near[0] < config.floor_color.0, "{:?} {:?}", near, far);
+}
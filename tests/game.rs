@@ -0,0 +1,207 @@
+mod common;
+
+use doomie::{
+    ai::AiState,
+    conf::{Config, TICK_RATE},
+    demo::Demo,
+    engine::Controlls::{self, *},
+    game::Game,
+    input::{Axis, InputState},
+    observer::EnemyStatus,
+};
+
+// the first level as it is
+fn game(script: Vec<InputState>) -> Box<Game<'static>> {
+    common::game(&common::level1(), script)
+}
+
+fn player_speed() -> f32 {
+    Config::default().player_speed
+}
+
+fn hold(controls: &[Controlls], ticks: usize) -> Vec<InputState> {
+    vec![InputState::holding(controls); ticks]
+}
+
+fn spin(dx: i32) -> InputState {
+    InputState::new().with_mouse(800, dx)
+}
+
+#[test]
+fn no_input_keeps_the_player_still() {
+    let mut game = game(vec![]);
+
+    assert_eq!(game.run_ticks(10), 10);
+
+    let state = game.observe();
+
+    assert_eq!(state.player.pos, (1.5, 5.0));
+    assert_eq!(state.player.life, 100);
+    // the soldiers start behind the bricks, out of sight
+    assert!(state.enemies.iter().all(|enemy| enemy.status == EnemyStatus::Idle));
+}
+
+#[test]
+fn escape_stops_the_game() {
+    let mut script = hold(&[], 2);
+    script.extend(hold(&[Escape], 1));
+
+    let mut game = game(script);
+
+    assert_eq!(game.run_ticks(10), 2);
+}
+
+#[test]
+fn diagonals_move_as_fast_as_straight_lines() {
+    let mut game = game(hold(&[ArrowUp, ArrowRight], 10));
+
+    game.run_ticks(10);
+
+    let (x, y) = game.observe().player.pos;
+    let walked = (x - 1.5).hypot(y - 5.0);
+
+    assert!(x > 1.5 && y > 5.0);
+    assert!((walked - 10.0 * player_speed() / TICK_RATE as f32).abs() < 1e-3);
+}
+
+#[test]
+fn analog_stick_walks_proportionally_past_the_dead_zone() {
+    let mut game = game(vec![
+        InputState::new().with_axis(Axis::Strafe, 0.1),
+        InputState::new().with_axis(Axis::Strafe, 0.6),
+    ]);
+
+    game.run_ticks(1);
+    assert_eq!(game.observe().player.pos, (1.5, 5.0));
+
+    game.run_ticks(1);
+    let (_, y) = game.observe().player.pos;
+    assert!((y - (5.0 + 0.5 * player_speed() / TICK_RATE as f32)).abs() < 1e-4);
+}
+
+#[test]
+fn shoot_the_soldier_twice_and_it_dies() {
+    // leave the spawn corridor and face the soldier at (10.5, 3.5)
+    let mut script = hold(&[ArrowRight], 24);
+    script.extend(hold(&[ArrowUp], 100));
+    script.extend([spin(-40), spin(-4), InputState::new()]);
+    script.extend([InputState::holding(&[Enter]), InputState::new(), InputState::holding(&[Enter])]);
+
+    let ticks = script.len() as u32;
+
+    let mut game = game(script);
+
+    game.run_ticks(ticks + 1);
+
+    let soldier = game.observe().enemies[0];
+
+    assert!(soldier.life <= 0);
+    assert_eq!(soldier.status, EnemyStatus::Dead);
+}
+
+#[test]
+fn gunfire_alerts_the_soldiers_within_earshot() {
+    let mut game = game(vec![InputState::holding(&[Enter])]);
+
+    game.run_ticks(2);
+
+    let state = game.observe();
+
+    // the second soldier is just over HEARING_RANGE away
+    assert_eq!(state.enemies[0].ai, AiState::Alert);
+    assert_eq!(state.enemies[1].ai, AiState::Idle);
+}
+
+#[test]
+fn replaying_a_recording_reproduces_the_session() {
+    let mut script = hold(&[ArrowUp], 40);
+    script.extend([spin(12), InputState::new(), InputState::holding(&[Enter])]);
+    script.extend(hold(&[ArrowRight, ArrowDown], 80));
+    script.extend(hold(&[Escape], 1));
+
+    let mut recorded = game(script);
+    recorded.start_recording();
+    recorded.run_ticks(500);
+    let demo = recorded.stop_recording().unwrap();
+
+    let demo = Demo::parse(&demo.to_string()).unwrap();
+
+    let mut replayed = game(vec![]);
+    replayed.play(demo).unwrap();
+    replayed.run_ticks(500);
+
+    assert_eq!(replayed.divergence(), None);
+    assert_eq!(replayed.observe().checksum(), recorded.observe().checksum());
+}
+
+#[test]
+fn tampered_checksums_are_reported_as_divergence() {
+    let mut recorded = game(hold(&[ArrowUp], 130));
+    recorded.start_recording();
+    recorded.run_ticks(130);
+    let demo = recorded.stop_recording().unwrap().to_string();
+
+    let line = demo.lines().find(|line| line.starts_with("c 60 ")).unwrap();
+    let demo = demo.replace(line, "c 60 00000000");
+
+    let mut replayed = game(vec![]);
+    replayed.play(Demo::parse(&demo).unwrap()).unwrap();
+    replayed.run_ticks(130);
+
+    assert_eq!(replayed.divergence().map(|divergence| divergence.tick), Some(60));
+}
+
+#[test]
+fn replays_use_the_settings_they_were_recorded_with() {
+    let config = Config { player_speed: 5.0, mouse_sensitivity: 0.03, ..Config::default() };
+
+    let mut script = hold(&[ArrowUp], 30);
+    script.extend([spin(12), spin(-5)]);
+    script.extend(hold(&[ArrowRight], 40));
+
+    let mut recorded = common::configured_game(&common::level1(), config, script);
+    recorded.start_recording();
+    recorded.run_ticks(130);
+    let demo = Demo::parse(&recorded.stop_recording().unwrap().to_string()).unwrap();
+
+    let mut replayed = game(vec![]);
+    replayed.play(demo).unwrap();
+    replayed.run_ticks(130);
+
+    assert_eq!(replayed.divergence(), None);
+    assert_eq!(replayed.observe().checksum(), recorded.observe().checksum());
+}
+
+#[test]
+fn demos_of_another_map_or_window_width_are_refused() {
+    let mut recorded = game(hold(&[ArrowUp], 10));
+    recorded.start_recording();
+    recorded.run_ticks(10);
+    let demo = recorded.stop_recording().unwrap();
+
+    let mut elsewhere = common::game(&common::corridor(""), vec![]);
+    let error = elsewhere.play(demo.clone()).unwrap_err();
+
+    assert_eq!(error.to_string(), "the demo was recorded on another map");
+
+    let config = Config { width: 800, num_rays: 400, ..Config::default() };
+    let mut narrow = common::configured_game(&common::level1(), config, vec![]);
+    let error = narrow.play(demo).unwrap_err();
+
+    assert_eq!(error.to_string(), "the demo was recorded 1600 pixels wide, the window is 800");
+}
+
+#[test]
+fn run_simulates_fixed_ticks_until_escape() {
+    let mut script = hold(&[ArrowRight], 8);
+    script.extend(hold(&[Escape], 1));
+
+    let mut game = game(script);
+
+    game.run();
+
+    // 8 steps of 1 / TICK_RATE seconds at PLAYER_SPEED units per second
+    let (_, y) = game.observe().player.pos;
+
+    assert!((y - (5.0 + 8.0 * player_speed() / TICK_RATE as f32)).abs() < 1e-3);
+}
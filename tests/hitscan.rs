@@ -0,0 +1,75 @@
+mod common;
+
+use doomie::{
+    ai::AiState,
+    conf::Config,
+    enemy_kinds::EnemyDefinition,
+    engine::{Controlls::Enter, Sprite, TextureID},
+    input::InputState,
+};
+
+use common::{corridor, game, walled_in};
+
+// the player at the west end of a corridor looking east
+fn soldier_at(x: f32) -> String {
+    corridor(&format!("enemy soldier {} 1.5", x))
+}
+
+// small window so rendering hundreds of frames stays quick
+fn config() -> Config {
+    Config { width: 320, height: 180, num_rays: 160, ..Config::default() }
+}
+
+fn life(frame: &[Sprite]) -> u32 {
+    frame.iter().find_map(|command| match command {
+        Sprite::LifeCounter(life) => Some(*life),
+        _ => None,
+    }).unwrap()
+}
+
+fn soldier_texture(frame: &[Sprite]) -> Option<TextureID> {
+    frame.iter().find_map(|command| match command {
+        Sprite::Sprite(_, texture_id, ..) => Some(*texture_id),
+        _ => None,
+    })
+}
+
+#[test]
+fn shots_are_less_likely_to_hit_far_and_moving_targets() {
+    let soldier = EnemyDefinition::default();
+
+    assert!(soldier.hit_chance(1.0, 0.0) > soldier.hit_chance(6.0, 0.0));
+    assert!(soldier.hit_chance(3.0, 0.0) > soldier.hit_chance(3.0, 3.6));
+    assert_eq!(soldier.hit_chance(100.0, 0.0), 0.0);
+    assert!(soldier.hit_chance(0.0, 0.0) <= 1.0);
+}
+
+#[test]
+fn soldiers_shoot_from_across_the_room() {
+    let frames = common::frames(&soldier_at(7.5), config(), 600);
+
+    assert!(life(frames.last().unwrap()) < 100);
+}
+
+#[test]
+fn the_damage_lands_with_the_muzzle_flash() {
+    let frames = common::frames(&soldier_at(3.5), config(), 600);
+
+    let hurt = frames.iter().position(|frame| life(frame) < 100).expect("the soldier never hit");
+
+    assert_eq!(soldier_texture(&frames[hurt]), Some(TextureID::SoldierAttack2));
+    assert_eq!(soldier_texture(&frames[hurt - 1]), Some(TextureID::SoldierAttack1));
+}
+
+#[test]
+fn walls_block_the_shots() {
+    // the soldier hears the shot but it is walled in, it can never see the player
+    let mut game = game(&walled_in("player 1.5 1.5 0\nenemy soldier 3.5 3.5"), vec![InputState::holding(&[Enter])]);
+
+    game.run_ticks(600);
+
+    let state = game.observe();
+
+    assert_eq!(state.enemies[0].ai, AiState::Chase);
+    assert_eq!(state.player.life, 100);
+}
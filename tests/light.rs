@@ -0,0 +1,92 @@
+mod common;
+
+use doomie::{conf::Config, engine::{Driver, Sprite}, shading::Shade};
+
+use common::level;
+
+// shades are compared without the fog getting in the way
+fn no_fog() -> Config {
+    Config { fog_falloff: f32::MAX, ..Config::default() }
+}
+
+// shade of the wall column at the middle of the screen
+fn center_column(commands: &[Sprite], config: &Config) -> Shade {
+    commands.iter().find_map(|command| match command {
+        Sprite::Column(.., num, _, _, shade) if *num == config.half_num_rays() => Some(*shade),
+        _ => None,
+    }).unwrap()
+}
+
+#[test]
+fn east_and_west_faces_are_darker() {
+    let config = no_fog();
+
+    // looking at the south east corner, the east wall fills the left half and the south wall the right one
+    let commands = common::first_frame(&level("WWWWW\nW...W\nW...W\nW...W\nWWWWW", "", "player 2.5 2.5 45"), config);
+
+    let shades: Vec<(usize, Shade)> = commands.iter().filter_map(|command| match command {
+        Sprite::Column(.., num, _, _, shade) => Some((*num, *shade)),
+        _ => None,
+    }).collect();
+
+    for (num, shade) in shades {
+        if num < config.half_num_rays() - 5 {
+            assert_eq!(shade, Shade { light: 191, fog: 0 }, "column {}", num);
+        } else if num > config.half_num_rays() + 5 {
+            assert_eq!(shade, Shade { light: 255, fog: 0 }, "column {}", num);
+        }
+    }
+}
+
+#[test]
+fn sprites_get_the_light_of_the_wall_beside_them() {
+    let config = no_fog();
+
+    // the soldier stands in the dark row at the bottom, right in front of the wall
+    let grid = "WWWWW\nW...W\nW...W\nW:::W\nWWWWW";
+    let commands = common::first_frame(&level(grid, ": = floor light 0.5", "player 2.5 1.5 90\nenemy soldier 2.5 3.5"), config);
+
+    let soldier = commands.iter().find_map(|command| match command {
+        Sprite::Sprite(.., shade) => Some(*shade),
+        _ => None,
+    }).unwrap();
+
+    assert_eq!(soldier, Shade { light: 128, fog: 0 });
+    assert_eq!(center_column(&commands, &config), soldier);
+}
+
+#[test]
+fn walls_fade_into_the_fog_color() {
+    let corridor = level("WWWWWWWWW\nW.......W\nWWWWWWWWW", "", "player 1.5 1.5 0");
+
+    // what the wall looks like without fog, lit as a west face
+    let clear = center_pixel(&common::first_frame(&corridor, no_fog()), &no_fog());
+
+    for fog_color in [(0, 0, 0), (255, 255, 255), (0, 0, 200)] {
+        let config = Config { fog_color, fog_falloff: 6.0, ..Config::default() };
+        let commands = common::first_frame(&corridor, config);
+
+        let fog = center_column(&commands, &config).fog as u32;
+        let blend = |texel: u8, fog_color: u8| ((texel as u32 * (255 - fog) + fog_color as u32 * fog) / 255) as u8;
+
+        assert!(fog > 0 && fog < 255);
+        assert_eq!(
+            center_pixel(&commands, &config),
+            [blend(clear[0], fog_color.0), blend(clear[1], fog_color.1), blend(clear[2], fog_color.2), 255],
+        );
+
+        // and once the fog is thick enough only its color is left
+        let config = Config { fog_falloff: 0.1, ..config };
+        let (r, g, b) = fog_color;
+
+        assert_eq!(center_pixel(&common::first_frame(&corridor, config), &config), [r, g, b, 255]);
+    }
+}
+
+// color drawn in the middle of the screen
+fn center_pixel(commands: &[Sprite], config: &Config) -> [u8; 4] {
+    let mut engine = common::engine(*config);
+
+    engine.render(commands.to_vec());
+    engine.frame().pixel(config.half_num_rays() as u32 * config.scale(), config.half_height())
+}
@@ -0,0 +1,109 @@
+use doomie::{engine::TextureID, map::{
+    map::{Face, Map, MapObject, Surface},
+    pos::Pos,
+    spawn::Spawn,
+}};
+
+const LEGEND: &str = "
+[legend]
+W = wall
+B = bricks
+. = floor
+";
+
+fn parse(grid: &str) -> Result<Map, String> {
+    with_entities(grid, "player 1.5 1.5 0")
+}
+
+fn with_entities(grid: &str, entities: &str) -> Result<Map, String> {
+    Map::parse(&format!("[grid]\n{}\n{}\n[entities]\n{}", grid, LEGEND, entities)).map_err(|e| e.to_string())
+}
+
+#[test]
+fn the_grid_can_have_any_size() {
+    let map = parse("WWWWWWW\nW..B..W\nW.....W\nWWWWWWW").unwrap();
+
+    assert_eq!((map.width(), map.height()), (7, 4));
+    assert_eq!(map.tile(3, 1), Some(MapObject::B));
+    assert_eq!(map.tile(1, 2), Some(MapObject::F));
+    assert_eq!(map.tile(7, 0), None);
+}
+
+#[test]
+fn unknown_tiles_report_their_line_and_column() {
+    assert_eq!(parse("WWWW\nW.?W\nWWWW").unwrap_err(), "line 3, column 3: unknown tile '?'");
+}
+
+#[test]
+fn the_border_must_be_closed() {
+    assert_eq!(parse("WWWW\nW...\nWWWW").unwrap_err(), "line 3, column 4: the outer border must be closed");
+    assert_eq!(parse("WW.W\nW..W\nWWWW").unwrap_err(), "line 2, column 3: the outer border must be closed");
+}
+
+#[test]
+fn rows_must_have_the_same_width() {
+    assert_eq!(parse("WWWW\nW..W\nWWW").unwrap_err(), "line 4: expected 4 tiles, found 3");
+}
+
+#[test]
+fn entities_are_placed_where_the_file_says() {
+    let map = with_entities("WWWWWW\nW....W\nWWWWWW", "player 1.5 1.5 90\nenemy soldier 4.5 1.5").unwrap();
+
+    let (pos, angle) = map.player_start().unwrap();
+
+    assert_eq!(pos, Pos::new(1.5, 1.5));
+    assert!((angle - 90f32.to_radians()).abs() < 1e-6);
+    assert_eq!(map.spawns()[1], Spawn::Enemy("soldier".to_string(), Pos::new(4.5, 1.5)));
+}
+
+#[test]
+fn entities_must_stand_on_the_floor() {
+    let grid = "WWWWWW\nW.B..W\nWWWWWW";
+
+    // the entities section starts after the 3 grid rows, the 4 legend lines and its header
+    assert_eq!(with_entities(grid, "player 1.5 1.5 0\nenemy soldier 2.5 1.5").unwrap_err(),
+        "line 13: (2.5, 1.5) is not a floor tile");
+    assert_eq!(with_entities(grid, "enemy soldier 1.5 1.5").unwrap_err(), "the map has no player start");
+}
+
+#[test]
+fn walls_can_have_a_texture_per_face() {
+    let text = "[grid]\nWWW\nWXW\nW.W\nWWW\n[legend]\nW = wall\nX = bricks 3 5\n. = floor\n[entities]\nplayer 1.5 2.5 0";
+    let map = Map::parse(text).unwrap();
+
+    assert_eq!(map.get_texture(Pos::new(1.5, 1.5), Face::NorthSouth), Some(TextureID::Wall3));
+    assert_eq!(map.get_texture(Pos::new(1.5, 1.5), Face::EastWest), Some(TextureID::Wall5));
+    assert_eq!(map.get_texture(Pos::new(0.5, 0.5), Face::EastWest), Some(TextureID::Wall1));
+    assert_eq!(map.get_texture(Pos::new(1.5, 2.5), Face::NorthSouth), None);
+
+    let error = Map::parse(&text.replace("bricks 3 5", "bricks 9")).unwrap_err();
+
+    assert_eq!(error.to_string(), "line 8: unknown wall texture \"9\"");
+}
+
+#[test]
+fn floor_tiles_can_have_a_floor_and_a_ceiling() {
+    let text = "[grid]\nWWWW\nW.,W\nWWWW\n[legend]\nW = wall\n. = floor\n, = floor 2 sky\n[entities]\nplayer 1.5 1.5 0";
+    let map = Map::parse(text).unwrap();
+
+    assert_eq!((map.floor(1, 1), map.ceiling(1, 1)), (Surface::Flat, Surface::Flat));
+    assert_eq!((map.floor(2, 1), map.ceiling(2, 1)), (Surface::Texture(TextureID::Wall2), Surface::Sky));
+    assert!(map.has_surfaces());
+
+    let error = Map::parse(&text.replace("floor 2 sky", "floor sky")).unwrap_err();
+
+    assert_eq!(error.to_string(), "line 8: the sky can only be on the ceiling");
+}
+
+#[test]
+fn floor_tiles_can_cost_more_to_walk_over() {
+    let text = "[grid]\nWWWW\nW.~W\nWWWW\n[legend]\nW = wall\n. = floor\n~ = floor 4 light 0.5 cost 3\n[entities]\nplayer 1.5 1.5 0";
+    let map = Map::parse(text).unwrap();
+
+    assert_eq!((map.cost(1, 1), map.cost(2, 1)), (1.0, 3.0));
+    assert_eq!(map.light(Pos::new(2.5, 1.5)), 0.5);
+
+    let error = Map::parse(&text.replace("cost 3", "cost 0.5")).unwrap_err();
+
+    assert_eq!(error.to_string(), "line 8: walking costs start at 1, got 0.5");
+}
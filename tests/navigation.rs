@@ -0,0 +1,142 @@
+mod common;
+
+use doomie::map::{flow::FlowField, map::Map, path::{shortcut, PathFinding}, pos::{MapPos, Pos}};
+
+fn map(grid: &str) -> Map {
+    with_legend(grid, "")
+}
+
+fn with_legend(grid: &str, legend: &str) -> Map {
+    Map::parse(&common::level(grid, legend, "player 1.5 1.5 0")).unwrap()
+}
+
+fn tiles(tiles: &[(usize, usize)]) -> Vec<MapPos> {
+    tiles.iter().map(|&(x, y)| MapPos::new(x, y)).collect()
+}
+
+#[test]
+fn paths_go_from_start_to_goal() {
+    let pathfinding = PathFinding::new(&map("WWWWW\nW...W\nW...W\nW...W\nWWWWW"));
+
+    let path = pathfinding.find_path(MapPos::new(1, 1), MapPos::new(3, 3));
+
+    assert_eq!(path, Some(tiles(&[(1, 1), (2, 2), (3, 3)])));
+    assert_eq!(pathfinding.find_path(MapPos::new(1, 1), MapPos::new(1, 1)), Some(tiles(&[(1, 1)])));
+}
+
+#[test]
+fn diagonals_dont_cut_wall_corners() {
+    let pathfinding = PathFinding::new(&map("WWWWW\nW.WWW\nW...W\nWWWWW"));
+
+    let path = pathfinding.find_path(MapPos::new(1, 1), MapPos::new(2, 2));
+
+    assert_eq!(path, Some(tiles(&[(1, 1), (1, 2), (2, 2)])));
+}
+
+#[test]
+fn costly_tiles_are_walked_around() {
+    let grid = "WWWWWWW\nW.....W\nW.~~~.W\nWWWWWWW";
+    let (start, goal) = (MapPos::new(1, 2), MapPos::new(5, 2));
+
+    let plain = PathFinding::new(&with_legend(grid, "~ = floor"));
+    let hazard = PathFinding::new(&with_legend(grid, "~ = floor cost 10"));
+
+    assert_eq!(plain.find_path(start, goal), Some(tiles(&[(1, 2), (2, 2), (3, 2), (4, 2), (5, 2)])));
+    assert_eq!(hazard.find_path(start, goal), Some(tiles(&[(1, 2), (2, 1), (3, 1), (4, 1), (5, 2)])));
+}
+
+#[test]
+fn walled_off_tiles_have_no_path() {
+    let pathfinding = PathFinding::new(&map("WWWWWW\nW.W..W\nWWWWWW"));
+
+    assert_eq!(pathfinding.find_path(MapPos::new(1, 1), MapPos::new(4, 1)), None);
+    assert_eq!(pathfinding.find_path(MapPos::new(1, 1), MapPos::new(2, 1)), None);
+}
+
+#[test]
+fn shortcuts_keep_clear_of_walls_and_costly_floor() {
+    let grid = "WWWWWWW\nW.....W\nW.W...W\nW..~..W\nWWWWWWW";
+    let map = with_legend(grid, "~ = floor cost 10");
+
+    assert!(shortcut(&map, Pos::new(1.5, 1.5), Pos::new(5.5, 1.5), 0.3));
+    // the centre gets past the corner of the wall, the side of the circle doesn't
+    assert!(!shortcut(&map, Pos::new(1.5, 1.5), Pos::new(5.5, 2.5), 0.3));
+    assert!(shortcut(&map, Pos::new(1.5, 1.5), Pos::new(5.5, 2.5), 0.05));
+    // across the costly tile, unless it starts on one
+    assert!(!shortcut(&map, Pos::new(1.5, 3.5), Pos::new(5.5, 3.5), 0.3));
+    assert!(shortcut(&map, Pos::new(3.5, 3.5), Pos::new(5.5, 3.5), 0.3));
+}
+
+#[test]
+fn distances_are_the_cost_of_the_cheapest_route() {
+    let map = map("WWWWWW\nW....W\nW.WW.W\nW....W\nWWWWWW");
+    let mut field = FlowField::new(&map);
+
+    assert!(field.update(MapPos::new(1, 1)));
+
+    assert_eq!(field.distance(MapPos::new(1, 1)), Some(0.0));
+    assert_eq!(field.distance(MapPos::new(4, 1)), Some(3.0));
+    assert_eq!(field.distance(MapPos::new(4, 3)), Some(5.0));
+    assert_eq!(field.distance(MapPos::new(2, 2)), None);
+}
+
+#[test]
+fn it_is_only_recomputed_when_the_goal_changes_tile() {
+    let map = map("WWWWW\nW...W\nWWWWW");
+    let mut field = FlowField::new(&map);
+
+    assert!(field.update(MapPos::new(1, 1)));
+    assert!(!field.update(MapPos::new(1, 1)));
+    assert!(field.update(MapPos::new(3, 1)));
+
+    assert_eq!(field.distance(MapPos::new(1, 1)), Some(2.0));
+}
+
+#[test]
+fn steps_go_downhill_around_taken_tiles() {
+    let map = map("WWWWWW\nW....W\nW....W\nWWWWWW");
+    let mut field = FlowField::new(&map);
+
+    field.update(MapPos::new(4, 1));
+
+    assert_eq!(field.next_step(MapPos::new(1, 1)), Some(MapPos::new(2, 1)));
+
+    field.occupy([MapPos::new(2, 1)]);
+    assert_eq!(field.next_step(MapPos::new(1, 1)), Some(MapPos::new(2, 2)));
+
+    field.occupy([MapPos::new(2, 1), MapPos::new(2, 2)]);
+    assert_eq!(field.next_step(MapPos::new(1, 1)), None);
+    assert!(field.taken(MapPos::new(2, 2)));
+
+    assert_eq!(field.next_step(MapPos::new(4, 1)), Some(MapPos::new(4, 1)));
+}
+
+#[test]
+fn steps_follow_the_same_routes_as_paths() {
+    let map = with_legend("WWWWWWW\nW.....W\nW.~~~.W\nWW....W\nWWWWWWW", "~ = floor cost 10");
+    let pathfinding = PathFinding::new(&map);
+    let mut field = FlowField::new(&map);
+
+    let (start, goal) = (MapPos::new(1, 2), MapPos::new(5, 2));
+
+    field.update(goal);
+
+    let mut walked = vec![start];
+
+    while *walked.last().unwrap() != goal {
+        walked.push(field.next_step(*walked.last().unwrap()).unwrap());
+    }
+
+    assert_eq!(Some(walked), pathfinding.find_path(start, goal));
+}
+
+#[test]
+fn unreachable_tiles_have_no_step() {
+    let map = map("WWWWWW\nW.W..W\nWWWWWW");
+    let mut field = FlowField::new(&map);
+
+    field.update(MapPos::new(4, 1));
+
+    assert_eq!(field.next_step(MapPos::new(1, 1)), None);
+    assert_eq!(field.next_step(MapPos::new(3, 1)), Some(MapPos::new(4, 1)));
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use doomie::{engine::Controlls::*, game::Game, input::InputState};
+
+use common::{game, level};
+
+// an open room three tiles tall, the player on the west side looking east
+fn room(entities: &str, script: Vec<InputState>) -> Box<Game<'static>> {
+    let grid = "WWWWWWWWWWWW\nW..........W\nW..........W\nW..........W\nWWWWWWWWWWWW";
+
+    game(&level(grid, "", &format!("player 1.5 1.5 0\n{}", entities)), script)
+}
+
+#[test]
+fn fireballs_fly_until_they_reach_the_player() {
+    let mut game = room("enemy caco_demon 6.5 1.5", vec![]);
+
+    // noticing the player takes a moment
+    while game.observe().projectiles.is_empty() {
+        game.run_ticks(1);
+    }
+
+    let start = game.observe().projectiles[0];
+
+    game.run_ticks(10);
+
+    let state = game.observe();
+
+    assert!(state.projectiles[0].0 < start.0, "the fireball moves towards the player");
+    assert_eq!(state.player.life, 100);
+
+    game.run_ticks(120);
+
+    let state = game.observe();
+
+    assert!(state.projectiles.is_empty());
+    assert!(state.player.life < 100);
+}
+
+#[test]
+fn fireballs_explode_against_walls() {
+    // the player steps out of the way once the fireball is thrown
+    let mut script = vec![InputState::new(); 110];
+    script.extend(vec![InputState::holding(&[ArrowRight]); 30]);
+
+    let mut game = room("enemy caco_demon 6.5 1.5", script);
+
+    game.run_ticks(250);
+
+    let state = game.observe();
+
+    assert!(state.player.pos.1 > 2.0, "the player moved to {:?}", state.player.pos);
+    assert!(state.projectiles.is_empty());
+    assert_eq!(state.player.life, 100);
+}
+
+#[test]
+fn fireballs_hit_enemies_in_the_way_but_not_their_owner() {
+    let mut game = room("enemy soldier 4.5 1.5\nenemy caco_demon 7.5 1.5", vec![]);
+
+    game.run_ticks(200);
+
+    let state = game.observe();
+
+    assert!(state.enemies[0].life < 100, "the soldier took the fireball");
+    assert_eq!(state.enemies[1].life, 150);
+}
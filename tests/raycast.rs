@@ -0,0 +1,160 @@
+mod common;
+
+use std::f32::consts::TAU;
+
+use doomie::map::{
+    map::{Face, Map},
+    pos::{MapPos, Pos},
+    raycasting::{cast_ray, line_of_sight},
+};
+use proptest::prelude::*;
+
+const MAX_DEPTH: u32 = 20;
+// step of the brute force samplers, and how close to a grid line counts as touching it
+const STEP: f32 = 1e-3;
+
+fn level1() -> Map {
+    Map::parse(&common::level1()).unwrap()
+}
+
+// 10x10 map with a closed border and walls where `walls` says inside, but for the
+// player start in the top left corner and the tiles of the given points
+fn random_map(walls: &[bool], open: &[Pos]) -> Map {
+    let rows: Vec<String> = (0..10).map(|y| {
+        (0..10).map(|x| {
+            let border = x == 0 || y == 0 || x == 9 || y == 9;
+            let open = (x, y) == (1, 1) || open.iter().any(|pos| (pos.map().x(), pos.map().y()) == (x, y));
+
+            if border || (!open && walls[(y - 1) * 8 + x - 1]) { 'W' } else { '.' }
+        }).collect()
+    }).collect();
+
+    Map::parse(&common::level(&rows.join("\n"), "", "player 1.5 1.5 0")).unwrap()
+}
+
+fn near_grid_line(value: f32) -> bool {
+    (value - value.round()).abs() < 2.0 * STEP
+}
+
+// walks the ray in tiny steps, the hit and the last point before it
+fn sample_ray(map: &Map, origin: Pos, angle: f32) -> Option<(f32, Pos, Pos)> {
+    let (sin_a, cos_a) = angle.sin_cos();
+    let mut previous = origin;
+
+    for step in 1..=(MAX_DEPTH as f32 / STEP) as usize {
+        let distance = step as f32 * STEP;
+        let point = Pos::new(origin.x() + distance * cos_a, origin.y() + distance * sin_a);
+
+        if map.is_wall(point) || point.x() < 0.0 || point.y() < 0.0 {
+            return Some((distance, previous, point));
+        }
+
+        previous = point;
+    }
+
+    None
+}
+
+fn check_ray(map: &Map, origin: Pos, angle: f32) -> Result<(), TestCaseError> {
+    prop_assume!(!map.is_wall(origin));
+
+    let hit = cast_ray(map, origin, angle, MAX_DEPTH);
+    let sampled = sample_ray(map, origin, angle);
+
+    // grazing a corner, the sampler can step over it and either side could be right
+    let (sin_a, cos_a) = angle.sin_cos();
+    let corner = |distance: f32| near_grid_line(origin.x() + distance * cos_a) && near_grid_line(origin.y() + distance * sin_a);
+
+    prop_assume!(!hit.is_some_and(|hit| corner(hit.distance)));
+    prop_assume!(!sampled.is_some_and(|(distance, ..)| corner(distance)));
+
+    let Some((distance, before, point)) = sampled else {
+        prop_assert!(hit.is_none_or(|hit| hit.distance > MAX_DEPTH as f32 - 2.0 * STEP), "{:?}", hit);
+        return Ok(());
+    };
+
+    prop_assume!(distance < MAX_DEPTH as f32 - 2.0 * STEP);
+
+    let hit = hit.expect("the sampler found a wall");
+
+    prop_assert!((hit.distance - distance).abs() <= 2.0 * STEP, "{} against {}", hit.distance, distance);
+    prop_assert_eq!(hit.cell, MapPos::new(point.x() as usize, point.y() as usize));
+    prop_assert!((0.0..=1.0).contains(&hit.offset));
+
+    let side = if before.map().x() != point.map().x() { Face::EastWest } else { Face::NorthSouth };
+
+    prop_assert_eq!(hit.side, side);
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn rays_hit_the_first_wall_on_the_first_level(x in 1.0f32..15.0, y in 1.0f32..8.0, angle in 0.0f32..TAU) {
+        check_ray(&level1(), Pos::new(x, y), angle)?;
+    }
+
+    #[test]
+    fn rays_hit_the_first_wall_on_random_maps(
+        walls in prop::collection::vec(prop::bool::weighted(0.3), 64),
+        x in 1.0f32..9.0, y in 1.0f32..9.0, angle in 0.0f32..TAU,
+    ) {
+        let origin = Pos::new(x, y);
+
+        check_ray(&random_map(&walls, &[origin]), origin, angle)?;
+    }
+
+    #[test]
+    fn line_of_sight_agrees_with_sampling_the_segment(
+        walls in prop::collection::vec(prop::bool::weighted(0.2), 64),
+        a in (1.0f32..9.0, 1.0f32..9.0), b in (1.0f32..9.0, 1.0f32..9.0),
+    ) {
+        let (a, b) = (Pos::new(a.0, a.1), Pos::new(b.0, b.1));
+        let map = random_map(&walls, &[a, b]);
+
+        let d = b - a;
+        let steps = (d.x().hypot(d.y()) / STEP) as usize;
+
+        let points = (0..=steps).map(|step| {
+            let t = step as f32 / steps.max(1) as f32;
+
+            Pos::new(a.x() + d.x() * t, a.y() + d.y() * t)
+        });
+
+        // points a hair away in any direction, to tell when the segment grazes a wall
+        let (mut inside, mut grazing) = (false, false);
+
+        for point in points {
+            inside |= map.is_wall(point);
+            grazing |= [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)].iter()
+                .any(|(dx, dy)| map.is_wall(Pos::new(point.x() + dx * 2.0 * STEP, point.y() + dy * 2.0 * STEP)));
+        }
+
+        prop_assume!(inside == grazing);
+
+        prop_assert_eq!(line_of_sight(&map, a, b), !inside);
+    }
+}
+
+#[test]
+fn rays_stop_at_max_depth() {
+    let map = level1();
+
+    // along the top row, the east wall is 13.5 tiles away
+    assert!(cast_ray(&map, Pos::new(1.5, 1.5), 0.0, 10).is_none());
+
+    let hit = cast_ray(&map, Pos::new(1.5, 1.5), 0.0, 20).unwrap();
+
+    assert_eq!((hit.distance, hit.cell, hit.side), (13.5, MapPos::new(15, 1), Face::EastWest));
+    assert_eq!(hit.offset, 0.5);
+}
+
+#[test]
+fn the_bricks_block_the_sight_of_the_soldiers() {
+    let map = level1();
+
+    // the first soldier stands behind the bricks at (6, 3..4)
+    assert!(!line_of_sight(&map, Pos::new(1.5, 3.5), Pos::new(10.5, 3.5)));
+    assert!(line_of_sight(&map, Pos::new(10.5, 6.5), Pos::new(10.5, 3.5)));
+    assert!(line_of_sight(&map, Pos::new(2.5, 2.5), Pos::new(2.5, 2.5)));
+}
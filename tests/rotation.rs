@@ -0,0 +1,28 @@
+mod common;
+
+use doomie::{conf::Config, engine::{Sprite, TextureID}};
+
+// the soldier is walled in, it can't reach the player so it stands still facing east
+fn soldier_seen_from(player: &str) -> TextureID {
+    let level = common::walled_in(&format!("{}\nenemy soldier 3.5 3.5", player));
+
+    common::first_frame(&level, Config::default()).iter().find_map(|command| match command {
+        Sprite::Sprite(_, texture_id, ..) => Some(*texture_id),
+        _ => None,
+    }).unwrap()
+}
+
+#[test]
+fn standing_soldiers_show_the_side_facing_the_camera() {
+    assert_eq!(soldier_seen_from("player 5.5 3.5 180"), TextureID::SoldierIdle1); // front
+    assert_eq!(soldier_seen_from("player 3.5 1.5 90"), TextureID::SoldierIdle3);  // its right side
+    assert_eq!(soldier_seen_from("player 1.5 3.5 0"), TextureID::SoldierIdle5);   // back
+    assert_eq!(soldier_seen_from("player 3.5 5.5 270"), TextureID::SoldierIdle7); // its left side
+}
+
+#[test]
+fn the_side_shown_does_not_change_when_the_camera_turns() {
+    // the soldier is off center but still on screen
+    assert_eq!(soldier_seen_from("player 5.5 3.5 200"), TextureID::SoldierIdle1);
+    assert_eq!(soldier_seen_from("player 5.5 3.5 160"), TextureID::SoldierIdle1);
+}
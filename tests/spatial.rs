@@ -0,0 +1,143 @@
+mod common;
+
+use doomie::{
+    conf::PLAYER_RADIUS,
+    engine::Controlls::{ArrowUp, Enter},
+    input::InputState,
+    map::pos::Pos,
+    spatial::{separation, Actor, SpatialHash},
+};
+
+use common::{corridor, game, level};
+
+fn actor(id: usize, x: f32, y: f32, radius: f32) -> Actor {
+    Actor { id, pos: Pos::new(x, y), radius }
+}
+
+fn hash(actors: &[Actor]) -> SpatialHash {
+    let mut hash = SpatialHash::default();
+
+    actors.iter().for_each(|actor| hash.insert(*actor));
+
+    hash
+}
+
+fn ids<'a>(actors: impl Iterator<Item = &'a Actor>) -> Vec<usize> {
+    let mut ids: Vec<usize> = actors.map(|actor| actor.id).collect();
+
+    ids.sort();
+    ids
+}
+
+#[test]
+fn only_the_actors_touching_the_circle_overlap_it() {
+    let hash = hash(&[actor(0, 2.5, 2.5, 0.3), actor(1, 3.2, 2.5, 0.3), actor(2, 9.5, 9.5, 0.3)]);
+
+    assert_eq!(ids(hash.overlapping(Pos::new(2.5, 2.9), 0.2)), vec![0]);
+    assert_eq!(ids(hash.overlapping(Pos::new(2.85, 2.5), 0.1)), vec![0, 1]);
+    assert_eq!(ids(hash.overlapping(Pos::new(6.0, 6.0), 1.0)), Vec::<usize>::new());
+}
+
+#[test]
+fn large_actors_are_found_from_neighbouring_tiles() {
+    let hash = hash(&[actor(0, 2.1, 2.5, 0.45)]);
+
+    assert_eq!(ids(hash.overlapping(Pos::new(1.5, 2.5), 0.2)), vec![0]);
+}
+
+#[test]
+fn lines_cross_actors_nearest_first() {
+    let hash = hash(&[actor(0, 6.5, 1.5, 0.3), actor(1, 3.5, 1.6, 0.3), actor(2, 3.5, 2.5, 0.3)]);
+
+    let hits = hash.along(Pos::new(1.5, 1.5), 0.0, 10.0);
+
+    assert_eq!(hits.iter().map(|(actor, _)| actor.id).collect::<Vec<_>>(), vec![1, 0]);
+    assert!((hits[1].1 - 4.7).abs() < 1e-4);
+
+    assert!(hash.along(Pos::new(1.5, 1.5), 0.0, 1.5).is_empty());
+}
+
+#[test]
+fn clearing_keeps_the_hash_usable() {
+    let mut hash = hash(&[actor(0, 2.5, 2.5, 0.3)]);
+
+    hash.clear();
+    assert_eq!(ids(hash.overlapping(Pos::new(2.5, 2.5), 0.1)), Vec::<usize>::new());
+
+    hash.insert(actor(1, 2.5, 2.5, 0.3));
+    assert_eq!(ids(hash.overlapping(Pos::new(2.5, 2.5), 0.1)), vec![1]);
+}
+
+#[test]
+fn separation_is_the_shortest_way_out() {
+    let other = actor(0, 2.5, 2.5, 0.3);
+
+    let away = separation(&other, Pos::new(2.9, 2.5), 0.2);
+
+    assert!((away.x() - 0.1).abs() < 1e-3 && away.y() == 0.0);
+    assert_eq!(separation(&other, Pos::new(3.5, 2.5), 0.2), Pos::new(0.0, 0.0));
+}
+
+#[test]
+fn living_enemies_block_the_player() {
+    let mut game = game(&corridor("enemy soldier 3.5 1.5"), vec![InputState::holding(&[ArrowUp]); 60]);
+
+    game.run_ticks(60);
+
+    let state = game.observe();
+    let gap = state.enemies[0].pos.0 - state.player.pos.0;
+
+    assert!(gap >= PLAYER_RADIUS + 0.3 - 1e-3, "the player got {} from the soldier", gap);
+    assert!(state.player.pos.0 > 2.5);
+}
+
+#[test]
+fn corpses_can_be_walked_over() {
+    // two shots, the second once the shotgun is ready again
+    let mut script = vec![InputState::new(), InputState::holding(&[Enter])];
+    script.extend(vec![InputState::new(); 60]);
+    script.push(InputState::holding(&[Enter]));
+    script.extend(vec![InputState::holding(&[ArrowUp]); 60]);
+
+    let ticks = script.len() as u32;
+    let mut game = game(&corridor("enemy soldier 3.5 1.5"), script);
+
+    game.run_ticks(ticks);
+
+    let state = game.observe();
+
+    assert!(state.enemies[0].life <= 0);
+    assert!(state.player.pos.0 > 4.0);
+}
+
+#[test]
+fn enemies_in_the_same_spot_push_apart() {
+    let mut game = game(&corridor("enemy soldier 5.5 1.5\nenemy soldier 5.5 1.5"), vec![]);
+
+    game.run_ticks(30);
+
+    let enemies = game.observe().enemies;
+    let (a, b) = (enemies[0].pos, enemies[1].pos);
+
+    assert!((a.0 - b.0).hypot(a.1 - b.1) >= 0.6 - 1e-3);
+}
+
+#[test]
+fn shots_only_hit_the_nearest_enemy_in_the_crosshair() {
+    let mut game = game(&corridor("enemy soldier 3.5 1.5\nenemy soldier 6.5 1.5"), vec![InputState::holding(&[Enter])]);
+
+    game.run_ticks(1);
+
+    let enemies = game.observe().enemies;
+
+    assert_eq!((enemies[0].life, enemies[1].life), (50, 100));
+}
+
+#[test]
+fn walls_stop_the_player_shots() {
+    let mut game = game(&level("WWWWWWWW\nW..W...W\nWWWWWWWW", "", "player 1.5 1.5 0\nenemy soldier 5.5 1.5"), vec![InputState::holding(&[Enter])]);
+
+    game.run_ticks(1);
+
+    assert_eq!(game.observe().enemies[0].life, 100);
+}